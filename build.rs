@@ -0,0 +1,7 @@
+fn main() {
+    glib_build_tools::compile_resources(
+        &["data"],
+        "src/resources.gresource.xml",
+        "fprintui.gresource",
+    );
+}
@@ -9,6 +9,7 @@ rustPlatform.buildRustPackage rec {
   nativeBuildInputs = [
     pkg-config
     wrapGAppsHook
+    glib
   ];
 
   buildInputs = [
@@ -19,6 +20,11 @@ rustPlatform.buildRustPackage rec {
     polkit
   ];
 
+  postInstall = ''
+    install -Dm644 data/org.example.fprintui.gschema.xml -t $out/share/gsettings-schemas/$name/glib-2.0/schemas
+    glib-compile-schemas $out/share/gsettings-schemas/$name/glib-2.0/schemas
+  '';
+
   postFixup = ''
     wrapProgram $out/bin/${pname} \
        --set LIBGL_ALWAYS_SOFTWARE true \
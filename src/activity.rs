@@ -0,0 +1,60 @@
+//! The session-only activity log shown in the Activity sidebar.
+
+use gtk4::glib;
+use gtk4::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivityKind {
+    Success,
+    Failure,
+    Hint,
+}
+
+impl ActivityKind {
+    fn tag_name(self) -> &'static str {
+        match self {
+            ActivityKind::Success => "success",
+            ActivityKind::Failure => "failure",
+            ActivityKind::Hint => "hint",
+        }
+    }
+}
+
+/// Timestamped, color-coded lines backed by a text buffer a `gtk4::TextView` can show.
+#[derive(Clone)]
+pub struct ActivityLog {
+    buffer: gtk4::TextBuffer,
+}
+
+impl Default for ActivityLog {
+    fn default() -> Self {
+        let buffer = gtk4::TextBuffer::new(None);
+        for (kind, color) in [
+            (ActivityKind::Success, "#26a269"),
+            (ActivityKind::Failure, "#c01c28"),
+            (ActivityKind::Hint, "#c88800"),
+        ] {
+            buffer.create_tag(Some(kind.tag_name()), &[("foreground", &color)]);
+        }
+        ActivityLog { buffer }
+    }
+}
+
+impl ActivityLog {
+    pub fn buffer(&self) -> &gtk4::TextBuffer {
+        &self.buffer
+    }
+
+    pub fn log(&self, kind: ActivityKind, message: &str) {
+        let timestamp = glib::DateTime::now_local()
+            .and_then(|now| now.format("%H:%M:%S"))
+            .map(|time| time.to_string())
+            .unwrap_or_default();
+        let mut end = self.buffer.end_iter();
+        self.buffer.insert_with_tags_by_name(
+            &mut end,
+            &format!("[{timestamp}] {message}\n"),
+            &[kind.tag_name()],
+        );
+    }
+}
@@ -0,0 +1,1122 @@
+use std::sync::RwLock;
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use gtk4::glib;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
+use zbus::names::{InterfaceName, WellKnownName};
+use zbus::proxy::Defaults;
+use zbus::zvariant::OwnedObjectPath;
+use zbus::{proxy, Connection};
+
+use crate::error::{fprintd_error_name, AppError};
+use crate::finger::FingerName;
+use crate::LOG_DOMAIN;
+
+/// Outcome of an enrollment session.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "result", content = "status", rename_all = "kebab-case")]
+pub enum EnrollResult {
+    Completed,
+    /// The user stopped the enrollment.
+    Cancelled,
+    /// Carries the raw fprintd status, e.g. `enroll-data-full`.
+    Error(String),
+}
+
+/// Outcome of a verification session.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "result", content = "status", rename_all = "kebab-case")]
+pub enum VerifyResult {
+    Match,
+    NoMatch,
+    /// No final status arrived in time.
+    TimedOut,
+    /// The user gave up before a final status arrived.
+    Cancelled,
+    /// Carries the raw fprintd status, e.g. `verify-disconnected`.
+    Error(String),
+}
+
+/// How the sensor expects the finger to be presented, from the device's `scan-type` property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanType {
+    Press,
+    Swipe,
+}
+
+impl ScanType {
+    pub fn from_property(value: &str) -> ScanType {
+        match value {
+            "swipe" => ScanType::Swipe,
+            _ => ScanType::Press,
+        }
+    }
+}
+
+/// Properties of the default device, as shown on the About Device page and copied into bug
+/// reports.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DeviceInfo {
+    pub name: String,
+    pub scan_type: String,
+    pub enroll_stages: i32,
+    pub path: String,
+    pub fprintd_version: Option<String>,
+    /// Only some drivers report this.
+    pub firmware_version: Option<String>,
+}
+
+// Results handed from tokio workers back to the UI thread by `run_off_main_thread`, and the
+// proxies and connection moved into those workers, must stay `Send`
+const _: () = {
+    const fn assert_send<T: Send + 'static>() {}
+    assert_send::<Connection>();
+    assert_send::<FPrintManagerProxy<'static>>();
+    assert_send::<FPrintDeviceProxy<'static>>();
+    assert_send::<DeviceInfo>();
+    assert_send::<EnrollResult>();
+    assert_send::<VerifyResult>();
+    assert_send::<PermissionLevel>();
+    assert_send::<AppError>();
+};
+
+/// Whether polkit lets the current user manage their own fingerprints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PermissionLevel {
+    #[default]
+    Full,
+    /// fprintd answered `PermissionDenied`, e.g. access is limited to administrators.
+    Restricted,
+}
+
+/// fprintd's bus name. The `#[proxy]` attributes below need literals, so these constants
+/// repeat them; [`check_names`] keeps both valid.
+pub const FPRINTD_SERVICE: &str = "net.reactivated.Fprint";
+pub const MANAGER_INTERFACE: &str = "net.reactivated.Fprint.Manager";
+pub const DEVICE_INTERFACE: &str = "net.reactivated.Fprint.Device";
+pub const MANAGER_PATH: &str = "/net/reactivated/Fprint/Manager";
+
+/// Catches a mistyped bus or interface name at startup in development builds, rather than as
+/// a cryptic zbus error on the first call.
+pub fn check_names() {
+    debug_assert!(WellKnownName::try_from(FPRINTD_SERVICE).is_ok());
+    for interface in [MANAGER_INTERFACE, DEVICE_INTERFACE] {
+        debug_assert!(
+            InterfaceName::try_from(interface).is_ok(),
+            "invalid interface name {interface}"
+        );
+    }
+    debug_assert_eq!(
+        FPrintManagerProxy::INTERFACE
+            .as_ref()
+            .map(|name| name.as_str()),
+        Some(MANAGER_INTERFACE)
+    );
+    debug_assert_eq!(
+        FPrintDeviceProxy::INTERFACE
+            .as_ref()
+            .map(|name| name.as_str()),
+        Some(DEVICE_INTERFACE)
+    );
+}
+
+#[proxy(
+    default_service = "net.reactivated.Fprint",
+    interface = "net.reactivated.Fprint.Manager",
+    default_path = "/net/reactivated/Fprint/Manager"
+)]
+pub trait FPrintManager {
+    fn get_devices(&self) -> zbus::Result<Vec<OwnedObjectPath>>;
+    fn get_default_device(&self) -> zbus::Result<OwnedObjectPath>;
+
+    #[zbus(property)]
+    fn version(&self) -> zbus::Result<String>;
+}
+
+// No default path: device paths are assigned by fprintd, so proxies are built through
+// [`device_proxy`] with the path the manager reports.
+#[proxy(
+    default_service = "net.reactivated.Fprint",
+    interface = "net.reactivated.Fprint.Device"
+)]
+pub trait FPrintDevice {
+    #[zbus(property, name = "name")]
+    fn name(&self) -> zbus::Result<String>;
+
+    fn list_enrolled_fingers(&self, username: &str) -> zbus::Result<Vec<String>>;
+
+    fn delete_enrolled_fingers(&self, finger: &str) -> zbus::Result<()>;
+    fn delete_enrolled_finger(&self, finger_name: &str) -> zbus::Result<()>;
+    fn delete_enrolled_fingers2(&self) -> zbus::Result<()>;
+
+    fn claim(&self, username: &str) -> zbus::Result<()>;
+    fn release(&self) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn enroll_status(&self, result: String, done: bool) -> zbus::Result<()>;
+
+    fn enroll_start(&self, finger_name: &str) -> zbus::Result<()>;
+
+    #[zbus(property, name = "num-enroll-stages")]
+    fn num_enroll_stages(&self) -> zbus::Result<i32>;
+
+    #[zbus(property, name = "scan-type")]
+    fn scan_type(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn firmware_version(&self) -> zbus::Result<String>;
+    fn enroll_stop(&self) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn verify_status(&self, result: String, done: bool) -> zbus::Result<()>;
+
+    fn verify_start(&self, finger_name: &str) -> zbus::Result<()>;
+    fn verify_stop(&self) -> zbus::Result<()>;
+}
+
+/// The device every proxy talks to, once resolved.
+static DEVICE_PATH: RwLock<Option<OwnedObjectPath>> = RwLock::new(None);
+
+/// The object path of the device in use, asking the manager for the default device the
+/// first time.
+pub async fn device_path(conn: &Connection) -> zbus::Result<OwnedObjectPath> {
+    // Bound first, so the lock guard is gone before the awaits below
+    let cached = DEVICE_PATH
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    if let Some(path) = cached {
+        return Ok(path);
+    }
+    let path = FPrintManagerProxy::new(conn)
+        .await?
+        .get_default_device()
+        .await?;
+    *DEVICE_PATH.write().unwrap_or_else(|e| e.into_inner()) = Some(path.clone());
+    Ok(path)
+}
+
+/// Switches every proxy created from now on to the device at `path`.
+pub fn set_device_path(path: OwnedObjectPath) {
+    *DEVICE_PATH.write().unwrap_or_else(|e| e.into_inner()) = Some(path);
+}
+
+/// Every device fprintd knows, with its `name` property, in the manager's order.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn list_devices(conn: &Connection) -> zbus::Result<Vec<(OwnedObjectPath, String)>> {
+    let paths = FPrintManagerProxy::new(conn).await?.get_devices().await?;
+    let mut devices = Vec::with_capacity(paths.len());
+    for path in paths {
+        let device = FPrintDeviceProxy::builder(conn)
+            .path(path.clone())?
+            .build()
+            .await?;
+        let name = device.name().await.unwrap_or_else(|_| path.to_string());
+        devices.push((path, name));
+    }
+    Ok(devices)
+}
+
+/// Forgets the resolved device, e.g. after fprintd restarted and may number devices anew.
+pub fn forget_device_path() {
+    *DEVICE_PATH.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// The user whose fingerprints are managed, when it isn't the one running the app.
+static TARGET_USER: RwLock<Option<String>> = RwLock::new(None);
+
+/// The user to pass to `Claim` and `ListEnrolledFingers`, by default the one running the app.
+pub fn target_user() -> String {
+    let user = TARGET_USER
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    user.unwrap_or_else(whoami::username)
+}
+
+/// Manages `username`'s fingerprints from now on. fprintd only allows this with the
+/// `setusername` polkit permission, and answers `PermissionDenied` otherwise.
+pub fn set_target_user(username: String) {
+    *TARGET_USER.write().unwrap_or_else(|e| e.into_inner()) = Some(username);
+}
+
+/// A proxy for the device in use, see [`device_path`].
+pub async fn device_proxy(conn: &Connection) -> zbus::Result<FPrintDeviceProxy<'static>> {
+    FPrintDeviceProxy::builder(conn)
+        .path(device_path(conn).await?)?
+        .build()
+        .await
+}
+
+const CONNECT_ATTEMPTS: u32 = 3;
+
+/// Whether `e` means the bus connection itself is gone, rather than a single failed call.
+pub fn is_connection_lost(e: &zbus::Error) -> bool {
+    matches!(e, zbus::Error::InputOutput(_))
+}
+
+/// The connection every caller of [`connect`] shares, once established.
+static SHARED_CONNECTION: RwLock<Option<Connection>> = RwLock::new(None);
+
+/// The app's system bus connection, connecting on first use.
+///
+/// Connecting retries briefly in case the bus daemon is restarting. Clones are cheap and all
+/// share one socket; call [`forget_connection`] once it turns out to be lost.
+#[tracing::instrument(level = "debug")]
+pub async fn connect() -> zbus::Result<Connection> {
+    // Bound first, so the lock guard is gone before the awaits below
+    let cached = SHARED_CONNECTION
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    if let Some(conn) = cached {
+        return Ok(conn);
+    }
+    let mut attempt = 1;
+    let conn = loop {
+        match Connection::system().await {
+            Err(e) if is_connection_lost(&e) && attempt < CONNECT_ATTEMPTS => {
+                attempt += 1;
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            result => break result?,
+        }
+    };
+    *SHARED_CONNECTION.write().unwrap_or_else(|e| e.into_inner()) = Some(conn.clone());
+    Ok(conn)
+}
+
+/// Drops the shared connection, so the next [`connect`] opens a new one.
+pub fn forget_connection() {
+    *SHARED_CONNECTION.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+const RECONNECT_ATTEMPTS: u32 = 3;
+
+/// Replaces a lost shared connection, trying up to [`RECONNECT_ATTEMPTS`] times a second
+/// apart. Returns whether fprintd answers on the new one.
+#[tracing::instrument(level = "debug")]
+pub async fn reconnect() -> bool {
+    for attempt in 1..=RECONNECT_ATTEMPTS {
+        forget_connection();
+        if ping_fprintd().await.is_ok() {
+            return true;
+        }
+        if attempt < RECONNECT_ATTEMPTS {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+    false
+}
+
+/// Whether fprintd can be reached at all. Pinging it also activates the service if needed.
+///
+/// A shared connection that was lost, e.g. because the bus daemon restarted, is replaced.
+#[tracing::instrument(level = "debug")]
+pub async fn fprintd_available() -> bool {
+    match ping_fprintd().await {
+        Err(e) if is_connection_lost(&e) => {
+            forget_connection();
+            ping_fprintd().await.is_ok()
+        }
+        result => result.is_ok(),
+    }
+}
+
+async fn ping_fprintd() -> zbus::Result<()> {
+    let conn = connect().await?;
+    let peer = zbus::fdo::PeerProxy::builder(&conn)
+        .destination(FPRINTD_SERVICE)?
+        .path(MANAGER_PATH)?
+        .build()
+        .await?;
+    peer.ping().await?;
+    Ok(())
+}
+
+/// Whether `e` means another client has the device claimed.
+pub fn is_device_busy(e: &zbus::Error) -> bool {
+    fprintd_error_name(e) == Some("AlreadyInUse")
+}
+
+/// How [`await_device_claim_release`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimWait {
+    /// The device is now claimed by us.
+    Claimed,
+    TimedOut,
+    Cancelled,
+}
+
+const CLAIM_RETRY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Retries claiming the device while another client holds it, until the claim goes through,
+/// `timeout` passes or `cancel` fires.
+///
+/// Errors other than the device being in use end the wait right away.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn await_device_claim_release(
+    proxy: &FPrintDeviceProxy<'_>,
+    timeout: Duration,
+    cancel: &CancellationToken,
+) -> zbus::Result<ClaimWait> {
+    let username = target_user();
+    let deadline = tokio::time::sleep(timeout);
+    tokio::pin!(deadline);
+    let mut retry = tokio::time::interval(CLAIM_RETRY_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = retry.tick() => {}
+            () = &mut deadline => return Ok(ClaimWait::TimedOut),
+            () = cancel.cancelled() => return Ok(ClaimWait::Cancelled),
+        }
+        match proxy.claim(&username).await {
+            Ok(()) => return Ok(ClaimWait::Claimed),
+            Err(e) if is_device_busy(&e) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Releases the device, treating "device not claimed" as success.
+///
+/// Cleanup paths can't always tell whether the claim went through, and fprintd rejects
+/// releasing an unclaimed device (`net.reactivated.Fprint.Error.ClaimDevice`). Any other
+/// error is passed on.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn release_if_claimed(proxy: &FPrintDeviceProxy<'_>) -> zbus::Result<()> {
+    match proxy.release().await {
+        Err(e) if matches!(fprintd_error_name(&e), Some("NotClaimed" | "ClaimDevice")) => Ok(()),
+        result => result,
+    }
+}
+
+/// The canonical names of `username`'s enrolled fingers, empty if there are none.
+#[tracing::instrument(level = "debug", skip_all, fields(%username))]
+pub async fn enrolled_fingers(
+    proxy: &FPrintDeviceProxy<'_>,
+    username: &str,
+) -> Result<Vec<String>, AppError> {
+    match proxy.list_enrolled_fingers(username).await {
+        // fprintd answers an empty list with an error
+        Err(e) if fprintd_error_name(&e) == Some("NoEnrolledPrints") => Ok(Vec::new()),
+        result => {
+            result.map_err(|e| AppError::dbus("calling FPrint.Device.ListEnrolledFingers", e))
+        }
+    }
+}
+
+/// Checks that `finger` is enrolled for `username` before starting a verification.
+///
+/// fprintd would otherwise just answer `verify-no-match`, which doesn't tell the user
+/// whether they used the wrong finger or never enrolled it.
+#[tracing::instrument(level = "debug", skip_all, fields(%username, %finger))]
+pub async fn ensure_finger_is_enrolled(
+    proxy: &FPrintDeviceProxy<'_>,
+    username: &str,
+    finger: &str,
+) -> Result<(), AppError> {
+    let finger = FingerName::try_from(finger)?;
+    let enrolled = enrolled_fingers(proxy, username).await?;
+
+    if enrolled.iter().any(|name| name == finger.as_str()) {
+        Ok(())
+    } else {
+        Err(AppError::FingerNotEnrolled(finger))
+    }
+}
+
+/// Starts verifying `finger` on the claimed device and waits for the outcome, giving up once
+/// `timeout` passes or `cancel` fires.
+///
+/// Whichever way it ends, the caller still has to call `verify_stop` and release the device.
+#[tracing::instrument(level = "debug", skip_all, fields(%finger))]
+pub async fn get_verification_result_with_timeout(
+    proxy: &FPrintDeviceProxy<'_>,
+    finger: &str,
+    timeout: Duration,
+    cancel: &CancellationToken,
+) -> VerifyResult {
+    // Subscribe first, so a status sent right after VerifyStart isn't missed
+    let mut statuses = match proxy.receive_verify_status().await {
+        Ok(statuses) => statuses,
+        Err(e) => return VerifyResult::Error(e.to_string()),
+    };
+    if let Err(e) = proxy.verify_start(finger).await {
+        return VerifyResult::Error(e.to_string());
+    }
+
+    let deadline = tokio::time::sleep(timeout);
+    tokio::pin!(deadline);
+    loop {
+        let msg = tokio::select! {
+            msg = statuses.next().instrument(tracing::debug_span!("await_stage")) => msg,
+            () = &mut deadline => return VerifyResult::TimedOut,
+            () = cancel.cancelled() => return VerifyResult::Cancelled,
+        };
+        // A stream that ends without a final status would otherwise leave us spinning here
+        let Some(msg) = msg else {
+            return VerifyResult::Error("Verification stream ended unexpectedly".into());
+        };
+        let args = match msg.args() {
+            Ok(args) => args,
+            Err(e) => return VerifyResult::Error(e.to_string()),
+        };
+        glib::g_debug!(
+            LOG_DOMAIN,
+            "Verify status: {} (done: {})",
+            args.result,
+            args.done
+        );
+
+        if !args.done {
+            continue;
+        }
+        match args.result.as_str() {
+            "verify-match" => return VerifyResult::Match,
+            "verify-no-match" => return VerifyResult::NoMatch,
+            "verify-retry-scan"
+            | "verify-swipe-too-short"
+            | "verify-finger-not-centered"
+            | "verify-remove-and-retry" => continue,
+            _ => return VerifyResult::Error(args.result),
+        }
+    }
+}
+
+/// Logs how long a span of work took at debug level, visible with `G_MESSAGES_DEBUG=fprintui`.
+///
+/// GLib keeps its sysprof mark API private, so this is the closest an application gets.
+struct TraceMark {
+    name: &'static str,
+    start: std::time::Instant,
+}
+
+impl TraceMark {
+    fn begin(name: &'static str) -> Self {
+        TraceMark {
+            name,
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+impl Drop for TraceMark {
+    fn drop(&mut self) {
+        glib::g_debug!(LOG_DOMAIN, "{}: {:?}", self.name, self.start.elapsed());
+    }
+}
+
+/// How often [`run_enroll_session`] checks whether enrollment stalled.
+const ENROLL_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Starts enrolling `finger` on the claimed device and follows it until it finishes, no status
+/// arrives for `stall_timeout` (retries included) or `cancel` fires. Every status is passed to
+/// `on_status` as it arrives.
+///
+/// Whichever way it ends, the session is stopped and the device released. Only a failure to
+/// start enrolling is returned as an error.
+#[tracing::instrument(level = "debug", skip_all, fields(%finger))]
+pub async fn run_enroll_session(
+    proxy: &FPrintDeviceProxy<'_>,
+    finger: &str,
+    stall_timeout: Duration,
+    cancel: &CancellationToken,
+    mut on_status: impl FnMut(&str),
+) -> Result<EnrollResult, AppError> {
+    // Subscribe first, so a status sent right after EnrollStart isn't missed
+    let started = async {
+        let statuses = proxy
+            .receive_enroll_status()
+            .await
+            .map_err(|e| AppError::dbus("subscribing to FPrint.Device.EnrollStatus", e))?;
+        let _start_mark = TraceMark::begin("dbus_enroll_start");
+        proxy.enroll_start(finger).await.map_err(|e| {
+            AppError::dbus(format!("calling FPrint.Device.EnrollStart for {finger}"), e)
+        })?;
+        Ok(statuses)
+    }
+    .await;
+    let mut statuses = match started {
+        Ok(statuses) => statuses,
+        Err(e) => {
+            if let Err(e) = release_if_claimed(proxy).await {
+                glib::g_warning!(LOG_DOMAIN, "Failed to release fingerprint device: {}", e);
+            }
+            return Err(e);
+        }
+    };
+
+    let mut heartbeat = tokio::time::interval(ENROLL_HEARTBEAT_INTERVAL);
+    heartbeat.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut last_status = tokio::time::Instant::now();
+    let result = loop {
+        let wait_mark = TraceMark::begin("enroll_stage_wait");
+        let next = loop {
+            tokio::select! {
+                msg = statuses.next().instrument(tracing::debug_span!("await_stage")) => {
+                    break Ok(msg);
+                }
+                () = cancel.cancelled() => break Err(EnrollResult::Cancelled),
+                _ = heartbeat.tick() => {
+                    if last_status.elapsed() >= stall_timeout {
+                        break Err(EnrollResult::Error(
+                            "Fingerprint sensor stopped responding.".into(),
+                        ));
+                    }
+                }
+            }
+        };
+        let msg = match next {
+            Ok(msg) => msg,
+            Err(result) => break result,
+        };
+        // A stream that ends without a final status would otherwise leave us spinning here
+        let Some(msg) = msg else {
+            break EnrollResult::Error("Enrollment stream ended unexpectedly".into());
+        };
+        drop(wait_mark);
+        last_status = tokio::time::Instant::now();
+        let _stage_mark = TraceMark::begin("enroll_stage");
+        let args = match msg.args() {
+            Ok(args) => args,
+            Err(e) => {
+                glib::g_warning!(LOG_DOMAIN, "Malformed EnrollStatus signal: {}", e);
+                break EnrollResult::Error(e.to_string());
+            }
+        };
+        glib::g_debug!(
+            LOG_DOMAIN,
+            "Enroll status: {} (done: {})",
+            args.result,
+            args.done
+        );
+        on_status(&args.result);
+
+        if !args.done {
+            continue;
+        }
+        match args.result.as_str() {
+            "enroll-completed" => break EnrollResult::Completed,
+            "enroll-stage-passed"
+            | "enroll-retry-scan"
+            | "enroll-swipe-too-short"
+            | "enroll-finger-not-centered"
+            | "enroll-remove-and-retry" => continue,
+            _ => break EnrollResult::Error(args.result),
+        }
+    };
+
+    let _ = proxy.enroll_stop().await;
+    if let Err(e) = release_if_claimed(proxy).await {
+        glib::g_warning!(LOG_DOMAIN, "Failed to release fingerprint device: {}", e);
+    }
+    Ok(result)
+}
+
+/// Fingers the device can enroll, if it restricts them.
+///
+/// Stock fprintd accepts any finger, so this is `None` unless the device object advertises a
+/// `supported-fingers` property. Names fprintui doesn't know are skipped.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_device_supported_fingers(
+    proxy: &FPrintDeviceProxy<'_>,
+) -> Option<Vec<FingerName>> {
+    let xml = proxy.inner().introspect().await.ok()?;
+    if !xml.contains("<property name=\"supported-fingers\"") {
+        return None;
+    }
+
+    let names: Vec<String> = proxy.inner().get_property("supported-fingers").await.ok()?;
+    Some(
+        names
+            .into_iter()
+            .filter_map(|name| FingerName::try_from(name).ok())
+            .collect(),
+    )
+}
+
+/// Probes whether polkit allows listing the current user's fingerprints.
+///
+/// Any failure other than `PermissionDenied` (no device, nothing enrolled) says nothing about
+/// permissions and counts as [`PermissionLevel::Full`].
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn check_fprintd_policykit_rules(conn: &Connection) -> PermissionLevel {
+    let Ok(proxy) = device_proxy(conn).await else {
+        return PermissionLevel::Full;
+    };
+    match proxy.list_enrolled_fingers(&target_user()).await {
+        Err(e) if fprintd_error_name(&e) == Some("PermissionDenied") => PermissionLevel::Restricted,
+        _ => PermissionLevel::Full,
+    }
+}
+
+/// Reads the properties of the device in use.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_device_info(conn: &Connection) -> zbus::Result<DeviceInfo> {
+    let path = device_path(conn).await?;
+    let device = FPrintDeviceProxy::builder(conn)
+        .path(path.clone())?
+        .build()
+        .await?;
+
+    Ok(DeviceInfo {
+        name: device.name().await?,
+        scan_type: device.scan_type().await?,
+        enroll_stages: device.num_enroll_stages().await?,
+        path: path.to_string(),
+        fprintd_version: get_fprintd_version(conn).await,
+        // Absent on most devices, which fprintd answers with UnknownProperty
+        firmware_version: device
+            .firmware_version()
+            .await
+            .ok()
+            .filter(|version| !version.is_empty()),
+    })
+}
+
+/// Device methods every fprintd from 1.92 onwards exposes. Deleting a single finger needs
+/// `DeleteEnrolledFinger`, which is the newest of them.
+const EXPECTED_DEVICE_METHODS: &[&str] = &[
+    "Claim",
+    "Release",
+    "ListEnrolledFingers",
+    "DeleteEnrolledFinger",
+    "DeleteEnrolledFingers2",
+    "EnrollStart",
+    "EnrollStop",
+    "VerifyStart",
+    "VerifyStop",
+];
+
+/// Returns the names of the methods the device in use exposes, as reported by
+/// `org.freedesktop.DBus.Introspectable`.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn device_methods(conn: &Connection) -> zbus::Result<Vec<String>> {
+    let device_path = device_path(conn).await?;
+
+    let introspectable = zbus::fdo::IntrospectableProxy::builder(conn)
+        .destination(FPRINTD_SERVICE)?
+        .path(device_path)?
+        .build()
+        .await?;
+    let xml = introspectable.introspect().await?;
+
+    Ok(xml
+        .split("<method name=\"")
+        .skip(1)
+        .filter_map(|rest| rest.split('"').next())
+        .map(String::from)
+        .collect())
+}
+
+/// Whether the running fprintd predates 1.92, judged by missing device methods.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn fprintd_is_outdated(conn: &Connection) -> zbus::Result<bool> {
+    let methods = device_methods(conn).await?;
+    Ok(EXPECTED_DEVICE_METHODS
+        .iter()
+        .any(|expected| !methods.iter().any(|method| method == expected)))
+}
+
+/// Best-effort fprintd version, for display purposes only.
+///
+/// Newer fprintd may expose a `Version` property on the manager. Without it, the minimum
+/// version is inferred from the device methods that are present.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_fprintd_version(conn: &Connection) -> Option<String> {
+    let manager = FPrintManagerProxy::new(conn).await.ok()?;
+    match manager.version().await {
+        Ok(version) => return Some(version),
+        // GDBus services answer unknown properties with InvalidArgs rather than UnknownProperty
+        Err(zbus::Error::FDO(e))
+            if matches!(
+                *e,
+                zbus::fdo::Error::UnknownProperty(_) | zbus::fdo::Error::InvalidArgs(_)
+            ) => {}
+        Err(_) => return None,
+    }
+
+    let methods = device_methods(conn).await.ok()?;
+    let has = |name: &str| methods.iter().any(|method| method == name);
+    if EXPECTED_DEVICE_METHODS.iter().all(|method| has(method)) {
+        Some("1.92 or later".to_string())
+    } else if has("DeleteEnrolledFingers2") {
+        // Everything but DeleteEnrolledFinger, which 1.92 added
+        Some("1.90 or later".to_string())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOCK_DEVICE_PATH: &str = "/net/reactivated/Fprint/Device/0";
+
+    /// Errors the mock answers with, named like fprintd's.
+    #[derive(Debug, zbus::DBusError)]
+    #[zbus(prefix = "net.reactivated.Fprint.Error")]
+    enum MockError {
+        #[zbus(error)]
+        ZBus(zbus::Error),
+        NoEnrolledPrints(String),
+        AlreadyInUse(String),
+        ClaimDevice(String),
+        Internal(String),
+    }
+
+    /// Stands in for fprintd's device object.
+    #[derive(Default)]
+    struct MockDevice {
+        enrolled: Vec<&'static str>,
+        /// Sent as `(result, done)` once verification starts.
+        verify_statuses: Vec<(&'static str, bool)>,
+        /// Sent as `(result, done)` once enrollment starts.
+        enroll_statuses: Vec<(&'static str, bool)>,
+        /// Enrollment fails to start, as with a sensor fprintd can't open.
+        enroll_start_fails: bool,
+        /// Another client holds the device, so claiming it fails.
+        busy: bool,
+        claimed: bool,
+        /// Receives the name of every claim, release and enrollment call.
+        calls: Option<tokio::sync::mpsc::UnboundedSender<&'static str>>,
+    }
+
+    impl MockDevice {
+        fn record(&self, call: &'static str) {
+            if let Some(calls) = &self.calls {
+                let _ = calls.send(call);
+            }
+        }
+    }
+
+    #[zbus::interface(name = "net.reactivated.Fprint.Device")]
+    impl MockDevice {
+        fn list_enrolled_fingers(&self, _username: &str) -> Result<Vec<String>, MockError> {
+            if self.enrolled.is_empty() {
+                return Err(MockError::NoEnrolledPrints(
+                    "No fingerprints enrolled".into(),
+                ));
+            }
+            Ok(self.enrolled.iter().map(|name| name.to_string()).collect())
+        }
+
+        fn claim(&mut self, _username: &str) -> Result<(), MockError> {
+            self.record("Claim");
+            if self.busy {
+                return Err(MockError::AlreadyInUse("Device was already claimed".into()));
+            }
+            self.claimed = true;
+            Ok(())
+        }
+
+        fn release(&mut self) -> Result<(), MockError> {
+            self.record("Release");
+            if !std::mem::take(&mut self.claimed) {
+                return Err(MockError::ClaimDevice(
+                    "Device was not claimed before use".into(),
+                ));
+            }
+            Ok(())
+        }
+
+        async fn verify_start(
+            &self,
+            #[zbus(signal_emitter)] emitter: zbus::object_server::SignalEmitter<'_>,
+            _finger_name: &str,
+        ) -> zbus::fdo::Result<()> {
+            for (result, done) in &self.verify_statuses {
+                Self::verify_status(&emitter, result, *done).await?;
+            }
+            Ok(())
+        }
+
+        async fn enroll_start(
+            &self,
+            #[zbus(signal_emitter)] emitter: zbus::object_server::SignalEmitter<'_>,
+            _finger_name: &str,
+        ) -> Result<(), MockError> {
+            self.record("EnrollStart");
+            if self.enroll_start_fails {
+                return Err(MockError::Internal("Failed to open the sensor".into()));
+            }
+            for (result, done) in &self.enroll_statuses {
+                Self::enroll_status(&emitter, result, *done).await?;
+            }
+            Ok(())
+        }
+
+        fn enroll_stop(&self) {
+            self.record("EnrollStop");
+        }
+
+        #[zbus(signal)]
+        async fn enroll_status(
+            emitter: &zbus::object_server::SignalEmitter<'_>,
+            result: &str,
+            done: bool,
+        ) -> zbus::Result<()>;
+
+        #[zbus(signal)]
+        async fn verify_status(
+            emitter: &zbus::object_server::SignalEmitter<'_>,
+            result: &str,
+            done: bool,
+        ) -> zbus::Result<()>;
+    }
+
+    /// A proxy for `device`, served over an in-process peer-to-peer connection. The returned
+    /// server connection has to be kept alive for as long as the proxy is used.
+    async fn mock_device_proxy(device: MockDevice) -> (Connection, FPrintDeviceProxy<'static>) {
+        let (client, server) = std::os::unix::net::UnixStream::pair().unwrap();
+        let server = zbus::connection::Builder::unix_stream(server)
+            .server(zbus::Guid::generate())
+            .unwrap()
+            .p2p()
+            .serve_at(MOCK_DEVICE_PATH, device)
+            .unwrap()
+            .build();
+        let client = zbus::connection::Builder::unix_stream(client).p2p().build();
+        let (server, client) = futures_util::future::try_join(server, client)
+            .await
+            .unwrap();
+        let proxy = FPrintDeviceProxy::builder(&client)
+            .path(MOCK_DEVICE_PATH)
+            .unwrap()
+            .cache_properties(zbus::proxy::CacheProperties::No)
+            .build()
+            .await
+            .unwrap();
+        (server, proxy)
+    }
+
+    async fn verify_with_statuses(statuses: Vec<(&'static str, bool)>) -> VerifyResult {
+        let (_server, proxy) = mock_device_proxy(MockDevice {
+            verify_statuses: statuses,
+            ..Default::default()
+        })
+        .await;
+        get_verification_result_with_timeout(
+            &proxy,
+            "right-index-finger",
+            Duration::from_millis(500),
+            &CancellationToken::new(),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn verify_waits_out_retry_statuses() {
+        let result = verify_with_statuses(vec![
+            ("verify-retry-scan", false),
+            ("verify-swipe-too-short", true),
+            ("verify-match", true),
+        ])
+        .await;
+        assert_eq!(result, VerifyResult::Match);
+    }
+
+    #[tokio::test]
+    async fn verify_reports_no_match_and_errors() {
+        let result = verify_with_statuses(vec![("verify-no-match", true)]).await;
+        assert_eq!(result, VerifyResult::NoMatch);
+        let result = verify_with_statuses(vec![("verify-disconnected", true)]).await;
+        assert_eq!(
+            result,
+            VerifyResult::Error("verify-disconnected".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_times_out_without_a_final_status() {
+        let result = verify_with_statuses(vec![("verify-retry-scan", false)]).await;
+        assert_eq!(result, VerifyResult::TimedOut);
+    }
+
+    #[tokio::test]
+    async fn no_enrolled_prints_is_an_empty_list() {
+        // The mock answers like fprintd, with a NoEnrolledPrints error
+        let (_server, proxy) = mock_device_proxy(MockDevice::default()).await;
+        assert!(proxy.list_enrolled_fingers("user").await.is_err());
+        let enrolled = enrolled_fingers(&proxy, "user").await.unwrap();
+        assert!(enrolled.is_empty(), "{enrolled:?}");
+    }
+
+    #[tokio::test]
+    async fn nothing_enrolled_means_finger_not_enrolled() {
+        let (_server, proxy) = mock_device_proxy(MockDevice::default()).await;
+        let result = ensure_finger_is_enrolled(&proxy, "user", "left-thumb").await;
+        assert!(matches!(
+            result,
+            Err(AppError::FingerNotEnrolled(FingerName::LeftThumb))
+        ));
+    }
+
+    /// Mock device calls made so far.
+    fn drain_calls(
+        calls: &mut tokio::sync::mpsc::UnboundedReceiver<&'static str>,
+    ) -> Vec<&'static str> {
+        std::iter::from_fn(|| calls.try_recv().ok()).collect()
+    }
+
+    #[tokio::test]
+    async fn claim_wait_stops_when_cancelled() {
+        let (tx, mut calls) = tokio::sync::mpsc::unbounded_channel();
+        let (_server, proxy) = mock_device_proxy(MockDevice {
+            busy: true,
+            calls: Some(tx),
+            ..Default::default()
+        })
+        .await;
+        let cancel = CancellationToken::new();
+        let canceller = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            canceller.cancel();
+        });
+        let wait = await_device_claim_release(&proxy, Duration::from_secs(30), &cancel).await;
+        assert_eq!(wait.unwrap(), ClaimWait::Cancelled);
+        // Cancelling beats the next retry, so the device was asked only once
+        assert_eq!(drain_calls(&mut calls), ["Claim"]);
+    }
+
+    #[tokio::test]
+    async fn claim_wait_claims_a_free_device() {
+        let (_server, proxy) = mock_device_proxy(MockDevice::default()).await;
+        let wait =
+            await_device_claim_release(&proxy, Duration::from_secs(30), &CancellationToken::new())
+                .await;
+        assert_eq!(wait.unwrap(), ClaimWait::Claimed);
+    }
+
+    #[tokio::test]
+    async fn release_if_claimed_releases_once() {
+        let (tx, mut calls) = tokio::sync::mpsc::unbounded_channel();
+        let (_server, proxy) = mock_device_proxy(MockDevice {
+            calls: Some(tx),
+            ..Default::default()
+        })
+        .await;
+        // Not claimed yet, which cleanup paths must not treat as a failure
+        release_if_claimed(&proxy).await.unwrap();
+        proxy.claim("user").await.unwrap();
+        release_if_claimed(&proxy).await.unwrap();
+        assert_eq!(drain_calls(&mut calls), ["Release", "Claim", "Release"]);
+    }
+
+    #[tokio::test]
+    async fn enrolled_fingers_map_to_finger_names() {
+        let (_server, proxy) = mock_device_proxy(MockDevice {
+            enrolled: vec!["right-index-finger", "left-thumb"],
+            ..Default::default()
+        })
+        .await;
+        let enrolled = enrolled_fingers(&proxy, "user").await.unwrap();
+        assert_eq!(enrolled, ["right-index-finger", "left-thumb"]);
+
+        let mut fingers: Vec<FingerName> = enrolled
+            .into_iter()
+            .map(|name| FingerName::try_from(name).unwrap())
+            .collect();
+        fingers.sort();
+        let display_names: Vec<_> = fingers.iter().map(FingerName::display_name).collect();
+        assert_eq!(display_names, ["Right Index Finger", "Left Thumb"]);
+
+        for finger in ["right-index-finger", "left-thumb"] {
+            let result = ensure_finger_is_enrolled(&proxy, "user", finger).await;
+            assert!(result.is_ok(), "{finger}: {result:?}");
+        }
+        assert!(matches!(
+            ensure_finger_is_enrolled(&proxy, "user", "right-thumb").await,
+            Err(AppError::FingerNotEnrolled(FingerName::RightThumb))
+        ));
+        assert!(matches!(
+            ensure_finger_is_enrolled(&proxy, "user", "thumb").await,
+            Err(AppError::InvalidFinger(_))
+        ));
+    }
+
+    /// Claims the mock device and runs an enrollment session on it, returning the outcome,
+    /// the statuses passed on and the device calls made.
+    async fn enroll_with(
+        device: MockDevice,
+        cancel: &CancellationToken,
+    ) -> (
+        Result<EnrollResult, AppError>,
+        Vec<String>,
+        Vec<&'static str>,
+    ) {
+        let (tx, mut calls) = tokio::sync::mpsc::unbounded_channel();
+        let (_server, proxy) = mock_device_proxy(MockDevice {
+            calls: Some(tx),
+            ..device
+        })
+        .await;
+        proxy.claim("user").await.unwrap();
+        let mut statuses = Vec::new();
+        let result = run_enroll_session(
+            &proxy,
+            "right-index-finger",
+            Duration::from_secs(30),
+            cancel,
+            |status| statuses.push(status.to_string()),
+        )
+        .await;
+        (result, statuses, drain_calls(&mut calls))
+    }
+
+    #[tokio::test]
+    async fn cancelling_enrollment_stops_and_releases_once() {
+        // No status ever arrives, as while waiting for a finger
+        let cancel = CancellationToken::new();
+        let canceller = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            canceller.cancel();
+        });
+        let (result, statuses, calls) = enroll_with(MockDevice::default(), &cancel).await;
+        assert_eq!(result.unwrap(), EnrollResult::Cancelled);
+        assert!(statuses.is_empty());
+        assert_eq!(calls, ["Claim", "EnrollStart", "EnrollStop", "Release"]);
+    }
+
+    #[tokio::test]
+    async fn enrollment_passes_on_statuses_until_completed() {
+        let device = MockDevice {
+            enroll_statuses: vec![
+                ("enroll-stage-passed", false),
+                ("enroll-retry-scan", false),
+                ("enroll-stage-passed", false),
+                ("enroll-completed", true),
+            ],
+            ..Default::default()
+        };
+        let (result, statuses, calls) = enroll_with(device, &CancellationToken::new()).await;
+        assert_eq!(result.unwrap(), EnrollResult::Completed);
+        assert_eq!(
+            statuses,
+            [
+                "enroll-stage-passed",
+                "enroll-retry-scan",
+                "enroll-stage-passed",
+                "enroll-completed"
+            ]
+        );
+        assert_eq!(calls, ["Claim", "EnrollStart", "EnrollStop", "Release"]);
+    }
+
+    #[tokio::test]
+    async fn enrollment_that_fails_to_start_releases_the_device() {
+        let device = MockDevice {
+            enroll_start_fails: true,
+            ..Default::default()
+        };
+        let (result, _, calls) = enroll_with(device, &CancellationToken::new()).await;
+        assert!(result.is_err());
+        assert_eq!(calls, ["Claim", "EnrollStart", "Release"]);
+    }
+}
@@ -0,0 +1,249 @@
+use std::fmt;
+
+use gtk4::glib;
+
+use crate::finger::{FingerName, InvalidFingerName};
+
+/// Errors worth telling the user about. `Display` is meant for dialogs: fprintd failures the
+/// user can act on get their own variants with a readable message, the rest stay [`AppError::DBus`].
+#[derive(Debug)]
+pub enum AppError {
+    FingerNotEnrolled(FingerName),
+    InvalidFinger(InvalidFingerName),
+    /// fprintd's `AlreadyInUse`: another application has the device claimed.
+    DeviceBusy,
+    /// fprintd's `NoSuchDevice`.
+    NoDevice,
+    /// fprintd's `PermissionDenied`, usually from polkit.
+    PermissionDenied,
+    /// The user stopped waiting for the device.
+    Cancelled,
+    DBus {
+        /// What was being attempted, e.g. `calling FPrint.Device.Claim`. May be empty.
+        context: String,
+        source: zbus::Error,
+    },
+}
+
+/// Codes for [`AppError`] within the `fprintui-fprint-error` GLib error domain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, glib::ErrorDomain)]
+#[error_domain(name = "fprintui-fprint-error")]
+pub enum AppErrorCode {
+    FingerNotEnrolled = 1,
+    InvalidFinger = 2,
+    DBus = 3,
+    DeviceBusy = 4,
+    NoDevice = 5,
+    PermissionDenied = 6,
+    Cancelled = 7,
+}
+
+/// Process exit statuses, following the usual Unix conventions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ExitStatus {
+    Success = 0,
+    Failure = 1,
+    Usage = 2,
+    NoDevice = 3,
+    PermissionDenied = 4,
+}
+
+impl From<ExitStatus> for std::process::ExitCode {
+    fn from(status: ExitStatus) -> Self {
+        std::process::ExitCode::from(status as u8)
+    }
+}
+
+impl From<ExitStatus> for glib::ExitCode {
+    fn from(status: ExitStatus) -> Self {
+        glib::ExitCode::from(status as i32)
+    }
+}
+
+impl AppError {
+    /// Wraps a D-Bus failure, picking out the fprintd errors that have a variant of their own.
+    /// Those don't need `context` to be understood.
+    pub fn dbus(context: impl Into<String>, source: zbus::Error) -> Self {
+        match fprintd_error_name(&source) {
+            Some("AlreadyInUse") => AppError::DeviceBusy,
+            Some("NoSuchDevice") => AppError::NoDevice,
+            Some("PermissionDenied") => AppError::PermissionDenied,
+            _ => AppError::DBus {
+                context: context.into(),
+                source,
+            },
+        }
+    }
+
+    /// Whether the bus connection itself is gone, see [`crate::dbus::is_connection_lost`].
+    pub fn is_connection_lost(&self) -> bool {
+        matches!(self, AppError::DBus { source, .. } if crate::dbus::is_connection_lost(source))
+    }
+
+    pub fn code(&self) -> AppErrorCode {
+        match self {
+            AppError::FingerNotEnrolled(_) => AppErrorCode::FingerNotEnrolled,
+            AppError::InvalidFinger(_) => AppErrorCode::InvalidFinger,
+            AppError::DeviceBusy => AppErrorCode::DeviceBusy,
+            AppError::NoDevice => AppErrorCode::NoDevice,
+            AppError::PermissionDenied => AppErrorCode::PermissionDenied,
+            AppError::Cancelled => AppErrorCode::Cancelled,
+            AppError::DBus { .. } => AppErrorCode::DBus,
+        }
+    }
+
+    pub fn exit_status(&self) -> ExitStatus {
+        match self {
+            AppError::FingerNotEnrolled(_) => ExitStatus::Failure,
+            AppError::InvalidFinger(_) => ExitStatus::Usage,
+            AppError::DeviceBusy | AppError::Cancelled => ExitStatus::Failure,
+            AppError::NoDevice => ExitStatus::NoDevice,
+            AppError::PermissionDenied => ExitStatus::PermissionDenied,
+            // The fprintd errors scripts care about have variants of their own, see `dbus`
+            AppError::DBus { .. } => ExitStatus::Failure,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::FingerNotEnrolled(finger) => {
+                write!(f, "The {} is not enrolled", finger.display_name())
+            }
+            AppError::InvalidFinger(e) => e.fmt(f),
+            AppError::DeviceBusy => {
+                f.write_str("The fingerprint reader is in use by another application")
+            }
+            AppError::NoDevice => f.write_str("No fingerprint reader was found"),
+            AppError::PermissionDenied => {
+                f.write_str("You are not allowed to manage fingerprints on this system")
+            }
+            AppError::Cancelled => f.write_str("Stopped waiting for the fingerprint reader"),
+            AppError::DBus { context, source } if context.is_empty() => source.fmt(f),
+            AppError::DBus { context, source } => write!(f, "{context}: {source}"),
+        }
+    }
+}
+
+impl std::error::Error for AppError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AppError::FingerNotEnrolled(_)
+            | AppError::DeviceBusy
+            | AppError::NoDevice
+            | AppError::PermissionDenied
+            | AppError::Cancelled => None,
+            AppError::InvalidFinger(e) => Some(e),
+            AppError::DBus { source, .. } => Some(source),
+        }
+    }
+}
+
+/// The last segment of an fprintd error name, e.g. `AlreadyInUse` for
+/// `net.reactivated.Fprint.Error.AlreadyInUse`.
+pub(crate) fn fprintd_error_name(e: &zbus::Error) -> Option<&str> {
+    match e {
+        zbus::Error::MethodError(name, _, _) => {
+            name.as_str().strip_prefix("net.reactivated.Fprint.Error.")
+        }
+        _ => None,
+    }
+}
+
+impl From<zbus::Error> for AppError {
+    fn from(e: zbus::Error) -> Self {
+        AppError::dbus(String::new(), e)
+    }
+}
+
+impl From<InvalidFingerName> for AppError {
+    fn from(e: InvalidFingerName) -> Self {
+        AppError::InvalidFinger(e)
+    }
+}
+
+impl From<&AppError> for glib::Error {
+    fn from(e: &AppError) -> Self {
+        glib::Error::new(e.code(), &e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An error reply from fprintd, e.g. `method_error("NoSuchDevice")`.
+    fn method_error(name: &str) -> zbus::Error {
+        let name = zbus::names::ErrorName::try_from(format!("net.reactivated.Fprint.Error.{name}"))
+            .unwrap();
+        let call = zbus::Message::method_call("/net/reactivated/Fprint/Device/0", "Claim")
+            .unwrap()
+            .build(&())
+            .unwrap();
+        zbus::Error::MethodError(name.into(), Some("details".to_string()), call)
+    }
+
+    #[test]
+    fn exit_status_for_fprintd_errors() {
+        let exit_status = |e: zbus::Error| AppError::from(e).exit_status();
+        assert_eq!(
+            exit_status(method_error("NoSuchDevice")),
+            ExitStatus::NoDevice
+        );
+        assert_eq!(
+            exit_status(method_error("PermissionDenied")),
+            ExitStatus::PermissionDenied
+        );
+        assert_eq!(
+            exit_status(method_error("AlreadyInUse")),
+            ExitStatus::Failure
+        );
+        assert_eq!(exit_status(zbus::Error::InvalidReply), ExitStatus::Failure);
+    }
+
+    #[test]
+    fn fprintd_errors_get_their_own_variants() {
+        assert!(matches!(
+            AppError::dbus("claiming", method_error("AlreadyInUse")),
+            AppError::DeviceBusy
+        ));
+        assert!(matches!(
+            AppError::dbus("claiming", method_error("NoSuchDevice")),
+            AppError::NoDevice
+        ));
+        assert!(matches!(
+            AppError::from(method_error("PermissionDenied")),
+            AppError::PermissionDenied
+        ));
+        let other = AppError::dbus("claiming", method_error("Internal"));
+        assert!(matches!(other, AppError::DBus { ref context, .. } if context == "claiming"));
+        assert!(other.to_string().starts_with("claiming: "));
+    }
+
+    #[test]
+    fn exit_status_per_variant() {
+        let cases = [
+            (
+                AppError::FingerNotEnrolled(FingerName::LeftThumb),
+                ExitStatus::Failure,
+            ),
+            (
+                AppError::InvalidFinger(InvalidFingerName("thumb".to_string())),
+                ExitStatus::Usage,
+            ),
+            (AppError::DeviceBusy, ExitStatus::Failure),
+            (AppError::Cancelled, ExitStatus::Failure),
+            (AppError::NoDevice, ExitStatus::NoDevice),
+            (AppError::PermissionDenied, ExitStatus::PermissionDenied),
+            (
+                AppError::from(zbus::Error::InvalidReply),
+                ExitStatus::Failure,
+            ),
+        ];
+        for (error, status) in cases {
+            assert_eq!(error.exit_status(), status, "{error:?}");
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use std::fmt;
+
+/// A finger as understood by fprintd.
+///
+/// `Display` yields the canonical D-Bus name (e.g. `right-index-finger`), which is what
+/// must be passed to the device proxy. Use [`FingerName::display_name`] for user-facing text.
+/// Serde uses the canonical name too.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum FingerName {
+    LeftThumb,
+    LeftIndexFinger,
+    LeftMiddleFinger,
+    LeftRingFinger,
+    LeftLittleFinger,
+    RightThumb,
+    RightIndexFinger,
+    RightMiddleFinger,
+    RightRingFinger,
+    RightLittleFinger,
+}
+
+impl FingerName {
+    pub const ALL: [FingerName; 10] = [
+        FingerName::LeftThumb,
+        FingerName::LeftIndexFinger,
+        FingerName::LeftMiddleFinger,
+        FingerName::LeftRingFinger,
+        FingerName::LeftLittleFinger,
+        FingerName::RightThumb,
+        FingerName::RightIndexFinger,
+        FingerName::RightMiddleFinger,
+        FingerName::RightRingFinger,
+        FingerName::RightLittleFinger,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FingerName::LeftThumb => "left-thumb",
+            FingerName::LeftIndexFinger => "left-index-finger",
+            FingerName::LeftMiddleFinger => "left-middle-finger",
+            FingerName::LeftRingFinger => "left-ring-finger",
+            FingerName::LeftLittleFinger => "left-little-finger",
+            FingerName::RightThumb => "right-thumb",
+            FingerName::RightIndexFinger => "right-index-finger",
+            FingerName::RightMiddleFinger => "right-middle-finger",
+            FingerName::RightRingFinger => "right-ring-finger",
+            FingerName::RightLittleFinger => "right-little-finger",
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            FingerName::LeftThumb => "Left Thumb",
+            FingerName::LeftIndexFinger => "Left Index Finger",
+            FingerName::LeftMiddleFinger => "Left Middle Finger",
+            FingerName::LeftRingFinger => "Left Ring Finger",
+            FingerName::LeftLittleFinger => "Left Little Finger",
+            FingerName::RightThumb => "Right Thumb",
+            FingerName::RightIndexFinger => "Right Index Finger",
+            FingerName::RightMiddleFinger => "Right Middle Finger",
+            FingerName::RightRingFinger => "Right Ring Finger",
+            FingerName::RightLittleFinger => "Right Little Finger",
+        }
+    }
+
+    /// Position in anatomical order: right hand before left, thumb to little finger.
+    pub fn priority(&self) -> u8 {
+        match self {
+            FingerName::RightThumb => 0,
+            FingerName::RightIndexFinger => 1,
+            FingerName::RightMiddleFinger => 2,
+            FingerName::RightRingFinger => 3,
+            FingerName::RightLittleFinger => 4,
+            FingerName::LeftThumb => 5,
+            FingerName::LeftIndexFinger => 6,
+            FingerName::LeftMiddleFinger => 7,
+            FingerName::LeftRingFinger => 8,
+            FingerName::LeftLittleFinger => 9,
+        }
+    }
+
+    /// Short form for space-constrained widgets.
+    pub fn abbreviated(&self) -> &'static str {
+        match self {
+            FingerName::LeftThumb => "L. Thumb",
+            FingerName::LeftIndexFinger => "L. Index",
+            FingerName::LeftMiddleFinger => "L. Middle",
+            FingerName::LeftRingFinger => "L. Ring",
+            FingerName::LeftLittleFinger => "L. Little",
+            FingerName::RightThumb => "R. Thumb",
+            FingerName::RightIndexFinger => "R. Index",
+            FingerName::RightMiddleFinger => "R. Middle",
+            FingerName::RightRingFinger => "R. Ring",
+            FingerName::RightLittleFinger => "R. Little",
+        }
+    }
+}
+
+/// Sorts fingers anatomically, see [`FingerName::priority`].
+impl Ord for FingerName {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.priority().cmp(&other.priority())
+    }
+}
+
+impl PartialOrd for FingerName {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for FingerName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<FingerName> for String {
+    fn from(finger: FingerName) -> Self {
+        finger.as_str().to_string()
+    }
+}
+
+/// A string that isn't one of fprintd's canonical finger names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidFingerName(pub String);
+
+impl fmt::Display for InvalidFingerName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let valid: Vec<&str> = FingerName::ALL.iter().map(FingerName::as_str).collect();
+        write!(
+            f,
+            "invalid finger name '{}', expected one of: {}",
+            self.0,
+            valid.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for InvalidFingerName {}
+
+impl TryFrom<&str> for FingerName {
+    type Error = InvalidFingerName;
+
+    fn try_from(name: &str) -> Result<Self, Self::Error> {
+        FingerName::ALL
+            .into_iter()
+            .find(|finger| finger.as_str() == name)
+            .ok_or_else(|| InvalidFingerName(name.to_string()))
+    }
+}
+
+impl TryFrom<String> for FingerName {
+    type Error = InvalidFingerName;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        FingerName::try_from(name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_canonical_name() {
+        for finger in FingerName::ALL {
+            assert_eq!(FingerName::try_from(finger.as_str()), Ok(finger));
+            assert_eq!(FingerName::try_from(finger.to_string()), Ok(finger));
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_names() {
+        for name in ["", "Right Index Finger", "right-index", "any"] {
+            assert_eq!(
+                FingerName::try_from(name),
+                Err(InvalidFingerName(name.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn sorts_anatomically() {
+        let mut fingers = FingerName::ALL;
+        fingers.sort();
+        assert_eq!(
+            fingers,
+            [
+                FingerName::RightThumb,
+                FingerName::RightIndexFinger,
+                FingerName::RightMiddleFinger,
+                FingerName::RightRingFinger,
+                FingerName::RightLittleFinger,
+                FingerName::LeftThumb,
+                FingerName::LeftIndexFinger,
+                FingerName::LeftMiddleFinger,
+                FingerName::LeftRingFinger,
+                FingerName::LeftLittleFinger,
+            ]
+        );
+    }
+
+    #[test]
+    fn ordering_agrees_with_priority() {
+        for a in FingerName::ALL {
+            for b in FingerName::ALL {
+                assert_eq!(a.cmp(&b), a.priority().cmp(&b.priority()));
+                assert_eq!(a.cmp(&b) == std::cmp::Ordering::Equal, a == b);
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_name_error_lists_valid_names() {
+        let message = InvalidFingerName("thumb".to_string()).to_string();
+        assert!(message.starts_with("invalid finger name 'thumb'"));
+        assert!(FingerName::ALL
+            .iter()
+            .all(|finger| message.contains(finger.as_str())));
+    }
+}
@@ -0,0 +1,53 @@
+use gtk4::glib;
+
+use crate::finger::FingerName;
+
+mod imp {
+    use std::cell::RefCell;
+
+    use gtk4::glib;
+    use gtk4::prelude::*;
+    use gtk4::subclass::prelude::*;
+
+    #[derive(Default, glib::Properties)]
+    #[properties(wrapper_type = super::FingerObject)]
+    pub struct FingerObject {
+        #[property(get, construct_only)]
+        canonical_name: RefCell<String>,
+        #[property(get, construct_only)]
+        display_name: RefCell<String>,
+        #[property(get, construct_only)]
+        short_name: RefCell<String>,
+        #[property(get, construct_only)]
+        icon_name: RefCell<String>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for FingerObject {
+        const NAME: &'static str = "FprintuiFingerObject";
+        type Type = super::FingerObject;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for FingerObject {}
+}
+
+glib::wrapper! {
+    /// List model item for a finger, so list widgets can bind to its names and icon.
+    pub struct FingerObject(ObjectSubclass<imp::FingerObject>);
+}
+
+impl FingerObject {
+    pub fn new(finger: FingerName, icon_name: &str) -> Self {
+        glib::Object::builder()
+            .property("canonical-name", finger.as_str())
+            .property("display-name", finger.display_name())
+            .property("short-name", finger.abbreviated())
+            .property("icon-name", icon_name)
+            .build()
+    }
+
+    pub fn finger(&self) -> Option<FingerName> {
+        FingerName::try_from(self.canonical_name()).ok()
+    }
+}
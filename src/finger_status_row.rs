@@ -0,0 +1,88 @@
+use gtk4::glib;
+use libadwaita as adw;
+
+use crate::finger::FingerName;
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use gtk4::glib::{self, closure};
+    use gtk4::prelude::*;
+    use gtk4::subclass::prelude::*;
+    use libadwaita as adw;
+    use libadwaita::subclass::prelude::*;
+
+    use crate::finger::FingerName;
+
+    #[derive(Default, glib::Properties)]
+    #[properties(wrapper_type = super::FingerStatusRow)]
+    pub struct FingerStatusRow {
+        /// Canonical fprintd name of the finger.
+        #[property(get, construct_only)]
+        finger: RefCell<String>,
+        #[property(get, set)]
+        enrolled: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for FingerStatusRow {
+        const NAME: &'static str = "FprintuiFingerStatusRow";
+        type Type = super::FingerStatusRow;
+        type ParentType = adw::ActionRow;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for FingerStatusRow {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let row = self.obj();
+
+            if let Ok(finger) = FingerName::try_from(row.finger()) {
+                row.set_title(finger.display_name());
+            }
+            let icon = gtk4::Image::from_icon_name(crate::get_finger_icon(&row.finger()));
+            row.add_prefix(&icon);
+
+            // Follow `enrolled` in place, so a refresh never has to rebuild the row
+            let enrolled = row.property_expression("enrolled");
+            enrolled
+                .chain_closure::<String>(closure!(|_: Option<glib::Object>, enrolled: bool| {
+                    if enrolled { "" } else { "(not enrolled)" }.to_string()
+                }))
+                .bind(&*row, "subtitle", gtk4::Widget::NONE);
+            enrolled
+                .chain_closure::<f64>(closure!(|_: Option<glib::Object>, enrolled: bool| {
+                    if enrolled {
+                        1.0
+                    } else {
+                        0.4
+                    }
+                }))
+                .bind(&icon, "opacity", gtk4::Widget::NONE);
+        }
+    }
+
+    impl WidgetImpl for FingerStatusRow {}
+    impl ListBoxRowImpl for FingerStatusRow {}
+    impl PreferencesRowImpl for FingerStatusRow {}
+    impl ActionRowImpl for FingerStatusRow {}
+}
+
+glib::wrapper! {
+    /// A finger in the enrolled fingers list, showing whether it's enrolled.
+    pub struct FingerStatusRow(ObjectSubclass<imp::FingerStatusRow>)
+        @extends adw::ActionRow, adw::PreferencesRow, gtk4::ListBoxRow, gtk4::Widget,
+        @implements gtk4::Accessible, gtk4::Actionable, gtk4::Buildable, gtk4::ConstraintTarget;
+}
+
+impl FingerStatusRow {
+    pub fn new(finger: FingerName) -> Self {
+        glib::Object::builder()
+            .property("finger", finger.as_str())
+            .build()
+    }
+
+    pub fn finger_name(&self) -> Option<FingerName> {
+        FingerName::try_from(self.finger()).ok()
+    }
+}
@@ -0,0 +1,195 @@
+//! A local log of enrollment and verification attempts and deletions, kept as JSON in the user data dir.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use gtk4::glib;
+use serde::{Deserialize, Serialize};
+
+use crate::dbus::{EnrollResult, VerifyResult};
+use crate::finger::FingerName;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HistoryEvent {
+    Enrolled(EnrollResult),
+    Verified(VerifyResult),
+    Deleted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub finger: FingerName,
+    pub timestamp: DateTime<Utc>,
+    pub event: HistoryEvent,
+}
+
+/// Held while the history file is rewritten or deleted, so two updates can't both start from
+/// the same old contents.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HistoryStore {
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl HistoryStore {
+    pub fn path() -> PathBuf {
+        glib::user_data_dir().join("fprintui").join("history.json")
+    }
+
+    /// Reads the history file at `path`, treating a missing file as an empty history.
+    fn load(path: &Path) -> anyhow::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json)
+                .with_context(|| format!("Malformed history file {}", path.display())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    /// Writes the history file at `path`, replacing it atomically so a crash can't leave it
+    /// truncated.
+    fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, path)
+            .with_context(|| format!("Failed to replace {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Deletes the history file.
+    pub fn clear() -> anyhow::Result<()> {
+        let path = Self::path();
+        let _lock = FILE_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to delete {}", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Appends one entry to the history file, logging rather than failing the caller.
+///
+/// The file is written on a blocking tokio thread, so the UI doesn't wait for the disk.
+pub fn record(finger: FingerName, event: HistoryEvent) {
+    let entry = HistoryEntry {
+        finger,
+        timestamp: Utc::now(),
+        event,
+    };
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = append(&HistoryStore::path(), entry) {
+            glib::g_warning!(crate::LOG_DOMAIN, "Failed to update history: {:#}", e);
+        }
+    });
+}
+
+fn append(path: &Path, entry: HistoryEntry) -> anyhow::Result<()> {
+    let _lock = FILE_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut store = match HistoryStore::load(path) {
+        Ok(store) => store,
+        // Rewriting a file we can't parse would lose it, so keep it for the user to look at
+        Err(e) if e.downcast_ref::<serde_json::Error>().is_some() => {
+            let aside = path.with_extension("json.bad");
+            fs::rename(path, &aside)
+                .with_context(|| format!("Failed to move {} aside", path.display()))?;
+            glib::g_warning!(
+                crate::LOG_DOMAIN,
+                "{:#}; moved it to {} and started a new history",
+                e,
+                aside.display()
+            );
+            HistoryStore::default()
+        }
+        Err(e) => return Err(e),
+    };
+    store.entries.push(entry);
+    store.save(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(event: HistoryEvent) -> HistoryEntry {
+        HistoryEntry {
+            finger: FingerName::RightIndexFinger,
+            timestamp: "2024-05-01T12:30:00Z".parse().unwrap(),
+            event,
+        }
+    }
+
+    #[test]
+    fn finger_serializes_as_canonical_name() {
+        assert_eq!(
+            serde_json::to_string(&FingerName::LeftLittleFinger).unwrap(),
+            "\"left-little-finger\""
+        );
+        assert!(serde_json::from_str::<FingerName>("\"little\"").is_err());
+    }
+
+    #[test]
+    fn entry_json_layout() {
+        let json = serde_json::to_value(entry(HistoryEvent::Enrolled(EnrollResult::Error(
+            "enroll-data-full".to_string(),
+        ))))
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "finger": "right-index-finger",
+                "timestamp": "2024-05-01T12:30:00Z",
+                "event": {"enrolled": {"result": "error", "status": "enroll-data-full"}},
+            })
+        );
+    }
+
+    #[test]
+    fn malformed_file_is_moved_aside() {
+        let dir = std::env::temp_dir().join(format!("fprintui-history-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("history.json");
+        fs::write(&path, "{ not json").unwrap();
+
+        append(&path, entry(HistoryEvent::Deleted)).unwrap();
+        assert_eq!(
+            fs::read_to_string(path.with_extension("json.bad")).unwrap(),
+            "{ not json"
+        );
+        assert_eq!(HistoryStore::load(&path).unwrap().entries.len(), 1);
+        append(&path, entry(HistoryEvent::Deleted)).unwrap();
+        assert_eq!(HistoryStore::load(&path).unwrap().entries.len(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn store_round_trips() {
+        let store = HistoryStore {
+            entries: vec![
+                entry(HistoryEvent::Enrolled(EnrollResult::Completed)),
+                entry(HistoryEvent::Verified(VerifyResult::NoMatch)),
+                entry(HistoryEvent::Verified(VerifyResult::Error(
+                    "verify-disconnected".to_string(),
+                ))),
+                entry(HistoryEvent::Deleted),
+            ],
+        };
+        let json = serde_json::to_string_pretty(&store).unwrap();
+        let loaded: HistoryStore = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string_pretty(&loaded).unwrap(), json);
+        assert_eq!(loaded.entries[0].finger, FingerName::RightIndexFinger);
+        assert_eq!(loaded.entries[0].timestamp, store.entries[0].timestamp);
+    }
+}
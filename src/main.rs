@@ -1,412 +1,1336 @@
-use futures_util::StreamExt;
+use anyhow::Context;
 use gtk4::glib::{self, ControlFlow};
 use gtk4::prelude::*;
-use gtk4::{
-    Application, ApplicationWindow, Box as GBox, Button, ComboBoxText, Image, Label, Orientation, Stack,
-};
+use gtk4::{gdk, gio};
+use gtk4::{Application, Box as GBox, Button, Image, Label, ListBox, Orientation, SelectionMode};
 use libadwaita as adw;
-use zbus::{proxy, Connection};
+use libadwaita::prelude::*;
+use libadwaita::ApplicationWindow;
+use tokio_util::sync::CancellationToken;
+
+mod activity;
+mod dbus;
+mod error;
+mod finger;
+mod finger_object;
+mod finger_status_row;
+mod history;
+mod notification;
+mod portal;
+mod settings;
+mod state;
+mod ui;
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::rc::Rc;
+
+use activity::ActivityKind;
+use dbus::{EnrollResult, FPrintDeviceProxy, PermissionLevel, ScanType, VerifyResult};
+use error::{fprintd_error_name, AppError, ExitStatus};
+use finger::FingerName;
+use finger_object::FingerObject;
+use finger_status_row::FingerStatusRow;
+use state::{AppEvent, AppState, DeviceReadiness, Operation};
+use ui::dialogs;
 
 const APP_ID: &str = "org.example.fprintui";
 
-#[proxy(
-    default_service = "net.reactivated.Fprint",
-    interface = "net.reactivated.Fprint.Device",
-    default_path = "/net/reactivated/Fprint/Device/0",
-)]
-trait FPrintDevice {
-    fn list_enrolled_fingers(&self, username: &str) -> zbus::Result<Vec<String>>;
-
-    fn delete_enrolled_fingers(&self, finger: &str) -> zbus::Result<()>;
-
-    fn claim(&self, username: &str) -> zbus::Result<()>;
-    fn release(&self) -> zbus::Result<()>;
-
-    #[zbus(signal)]
-    fn enroll_status(&self, result: String, done: bool) -> zbus::Result<()>;
-
-    fn enroll_start(&self, finger_name: &str) -> zbus::Result<()>;
-    fn enroll_stop(&self) -> zbus::Result<()>;
-
-    #[zbus(signal)]
-    fn verify_status(&self, result: String, done: bool) -> zbus::Result<()>;
-
-    fn verify_start(&self, finger_name: &str) -> zbus::Result<()>;
-    fn verify_stop(&self) -> zbus::Result<()>;
-}
-
-fn get_finger_icon(finger: &str) -> &str {
-    match finger {
-        "left-thumb" => "input-touchpad-symbolic",
-        "left-index-finger" => "input-mouse-symbolic",
-        "left-middle-finger" => "input-keyboard-symbolic",
-        "left-ring-finger" => "input-tablet-symbolic",
-        "left-little-finger" => "input-gaming-symbolic",
-        "right-thumb" => "input-touchpad-symbolic",
-        "right-index-finger" => "input-mouse-symbolic",
-        "right-middle-finger" => "input-keyboard-symbolic",
-        "right-ring-finger" => "input-tablet-symbolic",
-        "right-little-finger" => "input-gaming-symbolic",
-        _ => "dialog-question-symbolic",
-    }
-}
-
-fn create_finger_selector() -> ComboBoxText {
-    let combo = ComboBoxText::new();
-    let fingers = [
-        "left-thumb",
-        "left-index-finger",
-        "left-middle-finger",
-        "left-ring-finger",
-        "left-little-finger",
-        "right-thumb",
-        "right-index-finger",
-        "right-middle-finger",
-        "right-ring-finger",
-        "right-little-finger",
-    ];
+/// GLib log domain for the app's own messages, so they can be picked out with
+/// `G_MESSAGES_DEBUG=fprintui`.
+const LOG_DOMAIN: &str = "fprintui";
+
+fn load_css() {
+    let provider = gtk4::CssProvider::new();
+    provider.load_from_resource("/org/example/fprintui/style.css");
+    if let Some(display) = gdk::Display::default() {
+        gtk4::style_context_add_provider_for_display(
+            &display,
+            &provider,
+            gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
+        );
+    }
+}
 
-    for finger in fingers {
-        combo.append(Some(finger), finger);
-        if let Some(cell) = combo.last_child() {
-            if let Some(box_) = cell.first_child() {
-                if let Ok(box_container) = box_.downcast::<gtk4::Box>() {
-                    let icon = Image::from_icon_name(get_finger_icon(finger));
-                    icon.set_pixel_size(24);
-                    box_container.prepend(&icon);
-                }
-            }
+/// Icon for a finger, by its position on the hand.
+///
+/// The preferred names (`fingerprint-symbolic`, `pointer-primary-symbolic`,
+/// `pointer-secondary-symbolic`, `pointer-tertiary-symbolic`) aren't in every icon theme, so
+/// fall back to Adwaita's `auth-fingerprint-symbolic` when the current theme lacks them.
+fn get_finger_icon(finger: &str) -> &'static str {
+    const FALLBACK: &str = "auth-fingerprint-symbolic";
+
+    let preferred = match FingerName::try_from(finger) {
+        Ok(FingerName::LeftThumb | FingerName::RightThumb) => "fingerprint-symbolic",
+        Ok(FingerName::LeftIndexFinger | FingerName::RightIndexFinger) => {
+            "pointer-primary-symbolic"
         }
+        Ok(FingerName::LeftMiddleFinger | FingerName::RightMiddleFinger) => {
+            "pointer-secondary-symbolic"
+        }
+        Ok(
+            FingerName::LeftRingFinger
+            | FingerName::RightRingFinger
+            | FingerName::LeftLittleFinger
+            | FingerName::RightLittleFinger,
+        ) => "pointer-tertiary-symbolic",
+        Err(_) => return "dialog-question-symbolic",
+    };
+
+    let themed = gdk::Display::default()
+        .is_some_and(|display| gtk4::IconTheme::for_display(&display).has_icon(preferred));
+    if themed {
+        preferred
+    } else {
+        FALLBACK
     }
-
-    combo.set_active(Some(0));
-    combo
 }
 
-async fn handle_verification(window: &ApplicationWindow, finger_name: String) -> anyhow::Result<()> {
-    let conn = Connection::system().await?;
-    let proxy = FPrintDeviceProxy::new(&conn).await?;
+/// Builds list items showing a finger's icon and, if given, one of its [`FingerObject`] name
+/// properties.
+fn finger_item_factory(text_property: Option<&'static str>) -> gtk4::SignalListItemFactory {
+    let factory = gtk4::SignalListItemFactory::new();
+    factory.connect_setup(move |_, item| {
+        let Some(list_item) = item.downcast_ref::<gtk4::ListItem>() else {
+            return;
+        };
+        let row = GBox::new(Orientation::Horizontal, 6);
+        let icon = Image::new();
+        icon.set_pixel_size(24);
+        row.append(&icon);
+
+        let item_expression = gtk4::PropertyExpression::new(
+            gtk4::ListItem::static_type(),
+            None::<gtk4::Expression>,
+            "item",
+        );
+        gtk4::PropertyExpression::new(
+            FingerObject::static_type(),
+            Some(&item_expression),
+            "icon-name",
+        )
+        .bind(&icon, "icon-name", Some(list_item));
+
+        if let Some(property) = text_property {
+            let label = Label::new(None);
+            label.set_xalign(0.0);
+            row.append(&label);
+            gtk4::PropertyExpression::new(
+                FingerObject::static_type(),
+                Some(&item_expression),
+                property,
+            )
+            .bind(&label, "label", Some(list_item));
+        }
 
-    let dialog = gtk4::MessageDialog::new(
-        Some(window),
-        gtk4::DialogFlags::MODAL,
-        gtk4::MessageType::Info,
-        gtk4::ButtonsType::Cancel,
-        "Place your finger on the sensor to verify",
-    );
+        list_item.set_child(Some(&row));
+    });
+    factory
+}
 
-    let (sender, receiver) = async_channel::unbounded();
+fn create_finger_selector() -> gtk4::DropDown {
+    let model = gio::ListStore::new::<FingerObject>();
+    for finger in FingerName::ALL {
+        model.append(&FingerObject::new(finger, get_finger_icon(finger.as_str())));
+    }
 
-    dialog.connect_response(move |dialog, response| {
-        if response == gtk4::ResponseType::Cancel {
-            dialog.destroy();
+    let selector = gtk4::DropDown::builder()
+        .model(&model)
+        .factory(&finger_item_factory(Some("display-name")))
+        .expression(gtk4::PropertyExpression::new(
+            FingerObject::static_type(),
+            None::<gtk4::Expression>,
+            "display-name",
+        ))
+        .build();
+    if let Some(finger) = settings::preselected_finger() {
+        select_finger(&selector, finger);
+    }
+    filter_supported_fingers(&selector);
+    selector.connect_selected_notify(|selector| {
+        if SELECTING_FINGER.get() {
+            return;
+        }
+        if let Some(finger) = selected_finger(selector) {
+            settings::remember_selected_finger(finger);
         }
     });
+    selector
+}
 
-    dialog.show();
-
-    // Start verification in a separate thread
-    let sender = sender.clone();
-    glib::spawn_future_local(async move {
-        proxy.claim(&whoami::username()).await.unwrap();
-        let _ = proxy.verify_start(&finger_name.as_str()).await;
-        let mut verify_status_stream = proxy.receive_verify_status().await.unwrap();
-
-        let result = loop {if let Some(msg) = verify_status_stream.next().await {
-            // struct `JobNewArgs` is generated from `job_new` signal function arguments
-            let args = msg.args().expect("Error parsing message");
-
-            if !args.done {
-                continue;
-            }
+thread_local! {
+    /// Set while the app itself changes a finger selector, so only the user's own picks are
+    /// remembered as the last selected finger.
+    static SELECTING_FINGER: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
 
-            match dbg!(args.result.as_str()) {
-                "verify-match" => {
-                    break Ok(());
-                },
-                "verify-retry-scan" |
-                "verify-swipe-too-short" |
-                "verify-finger-not-centered" |
-                "verify-remove-and-retry" => continue,
-                _ => {
-                    break Err(args.result);
-                }
-            }
-        }};
+/// Runs `f`, which changes a finger selector's selection, without it counting as the user's
+/// pick.
+fn select_programmatically(f: impl FnOnce()) {
+    SELECTING_FINGER.set(true);
+    f();
+    SELECTING_FINGER.set(false);
+}
 
-        let _ = proxy.verify_stop().await;
-        let _ = proxy.release().await;
-        let _ = sender.send(result).await; // Send result back to main thread
-        drop(proxy);
+/// Drops the fingers the default device can't enroll from the selector, if it reports any.
+fn filter_supported_fingers(selector: &gtk4::DropDown) {
+    let Some(model) = selector.model().and_downcast::<gio::ListStore>() else {
+        return;
+    };
+    glib::spawn_future_local(async move {
+        let supported = run_off_main_thread(async {
+            let conn = dbus::connect()
+                .await
+                .context("connecting to the system bus")?;
+            let proxy = dbus::device_proxy(&conn)
+                .await
+                .context("creating the FPrint.Device proxy")?;
+            Ok(dbus::get_device_supported_fingers(&proxy).await)
+        })
+        .await;
+        if let Ok(Some(supported)) = supported {
+            select_programmatically(|| {
+                model.retain(|item| {
+                    item.downcast_ref::<FingerObject>()
+                        .and_then(FingerObject::finger)
+                        .is_some_and(|finger| supported.contains(&finger))
+                });
+            });
+        }
     });
+}
 
-    // Set up a recurring check for messages
-    let dialog_weak = dialog.downgrade();
+/// A device picker for machines with more than one reader; hidden while there is only one.
+///
+/// Picking a device points every proxy created afterwards at it and reloads the fingers.
+fn create_device_selector(state: &Rc<AppState>, window: &ApplicationWindow) -> GBox {
+    let names = gtk4::StringList::new(&[]);
+    let selector = gtk4::DropDown::builder()
+        .model(&names)
+        .hexpand(true)
+        .build();
+    let label = Label::new(Some("Device"));
+    let bar = GBox::new(Orientation::Horizontal, 10);
+    bar.set_margin_start(12);
+    bar.set_margin_end(12);
+    bar.set_margin_top(6);
+    bar.set_margin_bottom(6);
+    bar.append(&label);
+    bar.append(&selector);
+    bar.set_visible(false);
+    disable_while_busy(state, &selector);
+
+    let paths: Rc<RefCell<Vec<zbus::zvariant::OwnedObjectPath>>> = Rc::default();
+    // Set while the list is being refilled, so that doesn't count as picking a device
+    let loading = Rc::new(std::cell::Cell::new(false));
+
+    let selected_paths = paths.clone();
+    let selected_loading = loading.clone();
     let window_weak = window.downgrade();
-    glib::timeout_add_local(std::time::Duration::from_millis(100), move || {
-        let receiver = receiver.clone();
-        let dialog_weak = dialog_weak.clone();
-        let window_weak = window_weak.clone();
+    selector.connect_selected_notify(move |selector| {
+        if selected_loading.get() {
+            return;
+        }
+        let Some(path) = selected_paths
+            .borrow()
+            .get(selector.selected() as usize)
+            .cloned()
+        else {
+            return;
+        };
+        settings::remember_device_path(path.as_str());
+        dbus::set_device_path(path);
+        if let Some(window) = window_weak.upgrade() {
+            let _ = window.activate_action("win.refresh", None);
+        }
+    });
 
-        glib::spawn_future_local(async move {
-            if let Ok(result) = receiver.try_recv() {
-                if let Some(dialog) = dialog_weak.upgrade() {
-                    dialog.destroy();
+    let load = {
+        let bar_weak = bar.downgrade();
+        let selector_weak = selector.downgrade();
+        let window_weak = window.downgrade();
+        move || {
+            let bar_weak = bar_weak.clone();
+            let selector_weak = selector_weak.clone();
+            let window_weak = window_weak.clone();
+            let paths = paths.clone();
+            let loading = loading.clone();
+            glib::spawn_future_local(async move {
+                let result = run_off_main_thread(async {
+                    let conn = dbus::connect()
+                        .await
+                        .context("connecting to the system bus")?;
+                    let devices = dbus::list_devices(&conn)
+                        .await
+                        .context("calling FPrint.Manager.GetDevices")?;
+                    let current = dbus::device_path(&conn).await.ok();
+                    Ok((devices, current))
+                })
+                .await;
+                let (Some(bar), Some(selector)) = (bar_weak.upgrade(), selector_weak.upgrade())
+                else {
+                    return;
+                };
+                let (devices, mut current) = match result {
+                    Ok(loaded) => loaded,
+                    Err(e) => {
+                        glib::g_warning!(LOG_DOMAIN, "Failed to list fingerprint devices: {:#}", e);
+                        (Vec::new(), None)
+                    }
+                };
+                // Switch to the device picked in an earlier session, as long as it's still there
+                let preferred = settings::default_device_path().and_then(|saved| {
+                    devices
+                        .iter()
+                        .map(|(path, _)| path)
+                        .find(|path| path.as_str() == saved)
+                });
+                if let Some(path) = preferred.filter(|path| Some(*path) != current.as_ref()) {
+                    dbus::set_device_path(path.clone());
+                    current = Some(path.clone());
                     if let Some(window) = window_weak.upgrade() {
-                        match result {
-                            Ok(_) => {
-                                let success_dialog = gtk4::MessageDialog::new(
-                                    Some(&window),
-                                    gtk4::DialogFlags::MODAL,
-                                    gtk4::MessageType::Info,
-                                    gtk4::ButtonsType::Ok,
-                                    "Verification successful!",
-                                );
-                                success_dialog.show();
-                            }
-                            Err(e) => {
-                                let conn = Connection::system().await.unwrap();
-                                let proxy = FPrintDeviceProxy::new(&conn).await.unwrap();
-                                proxy.verify_stop().await.unwrap();
-                                proxy.release().await.unwrap();
-                                let error_dialog = gtk4::MessageDialog::new(
-                                    Some(&window),
-                                    gtk4::DialogFlags::MODAL,
-                                    gtk4::MessageType::Error,
-                                    gtk4::ButtonsType::Ok,
-                                    &format!("Verification failed: {}", e),
-                                );
-                                error_dialog.show();
-                            }
-                        }
+                        let _ = window.activate_action("win.refresh", None);
                     }
                 }
-                ControlFlow::Break
-            } else {
-                ControlFlow::Continue
-            }
-        });
-
+                loading.set(true);
+                if let Some(names) = selector.model().and_downcast::<gtk4::StringList>() {
+                    let labels: Vec<&str> = devices.iter().map(|(_, name)| name.as_str()).collect();
+                    names.splice(0, names.n_items(), &labels);
+                }
+                let position = devices
+                    .iter()
+                    .position(|(path, _)| Some(path) == current.as_ref());
+                selector.set_selected(position.unwrap_or(0) as u32);
+                paths.replace(devices.into_iter().map(|(path, _)| path).collect());
+                loading.set(false);
+                bar.set_visible(paths.borrow().len() > 1);
+            });
+        }
+    };
+    load();
+
+    // Readers come and go with fprintd, e.g. after a USB reader is plugged in
+    let bar_weak = bar.downgrade();
+    connect_app_events(state, move |event| {
+        if bar_weak.upgrade().is_none() {
+            return ControlFlow::Break;
+        }
+        if *event == AppEvent::DeviceStateChanged(DeviceReadiness::Available) {
+            load();
+        }
         ControlFlow::Continue
     });
 
-    Ok(())
+    bar
 }
 
-async fn handle_enrollment(window: &ApplicationWindow, finger_name: String) -> anyhow::Result<()> {
-    let conn = Connection::system().await?;
-    let proxy = FPrintDeviceProxy::new(&conn).await?;
+/// The finger currently chosen in a selector from [`create_finger_selector`].
+fn selected_finger(selector: &gtk4::DropDown) -> Option<FingerName> {
+    selector
+        .selected_item()
+        .and_downcast::<FingerObject>()
+        .and_then(|item| item.finger())
+}
 
-    let dialog = gtk4::MessageDialog::new(
-        Some(window),
-        gtk4::DialogFlags::MODAL,
-        gtk4::MessageType::Info,
-        gtk4::ButtonsType::Cancel,
-        "Place your finger on the sensor",
-    );
+fn select_finger(selector: &gtk4::DropDown, finger: FingerName) {
+    let Some(model) = selector.model() else {
+        return;
+    };
+    for position in 0..model.n_items() {
+        let item = model.item(position).and_downcast::<FingerObject>();
+        if item.and_then(|item| item.finger()) == Some(finger) {
+            select_programmatically(|| selector.set_selected(position));
+            return;
+        }
+    }
+}
+
+/// Wraps `selector` in a squeezer that falls back to abbreviated names, then to icons only,
+/// as the available width shrinks. All variants share the selector's model and selection.
+fn create_finger_selector_squeezer(selector: &gtk4::DropDown) -> adw::Squeezer {
+    let medium = gtk4::DropDown::new(selector.model(), None::<gtk4::Expression>);
+    medium.set_factory(Some(&finger_item_factory(Some("short-name"))));
+
+    let narrow = gtk4::DropDown::new(selector.model(), None::<gtk4::Expression>);
+    narrow.set_factory(Some(&finger_item_factory(None)));
+
+    for variant in [&medium, &narrow] {
+        selector
+            .bind_property("selected", variant, "selected")
+            .bidirectional()
+            .sync_create()
+            .build();
+    }
+
+    let squeezer = adw::Squeezer::builder()
+        .homogeneous(false)
+        .transition_type(adw::SqueezerTransitionType::Crossfade)
+        .build();
+    squeezer.add(selector);
+    squeezer.add(&medium);
+    squeezer.add(&narrow);
+    squeezer
+}
+
+/// Groups whose members may manage other users' fingerprints.
+const ADMIN_GROUPS: [&str; 2] = ["wheel", "sudo"];
+
+/// Whether the app runs as root or with an [`ADMIN_GROUPS`] group among its groups.
+///
+/// Only decides whether to offer picking another user; fprintd's polkit rules have the final
+/// say.
+fn is_admin() -> bool {
+    use nix::unistd::{getegid, geteuid, getgroups, Group};
+
+    if geteuid().is_root() {
+        return true;
+    }
+    let mut groups = getgroups().unwrap_or_default();
+    groups.push(getegid());
+    // Resolved through NSS, so LDAP and sssd groups count as well
+    let admin_gids: Vec<_> = ADMIN_GROUPS
+        .iter()
+        .filter_map(|name| Group::from_name(name).ok().flatten())
+        .map(|group| group.gid)
+        .collect();
+    has_admin_group(&groups, &admin_gids)
+}
+
+fn has_admin_group(groups: &[nix::unistd::Gid], admin_gids: &[nix::unistd::Gid]) -> bool {
+    groups.iter().any(|gid| admin_gids.contains(gid))
+}
 
-    let (sender, receiver) = async_channel::unbounded();
+/// A bar with the user whose fingerprints are managed. Administrators can enter another
+/// user and press Enter to switch to them; everyone else sees their own name.
+fn create_user_selector(state: &Rc<AppState>, window: &ApplicationWindow) -> GBox {
+    let entry = gtk4::Entry::builder()
+        .text(state.username.as_str())
+        .hexpand(true)
+        .build();
+    let label = Label::new(Some("User"));
+    let bar = GBox::new(Orientation::Horizontal, 10);
+    bar.set_margin_start(12);
+    bar.set_margin_end(12);
+    bar.set_margin_top(6);
+    bar.set_margin_bottom(6);
+    bar.append(&label);
+    bar.append(&entry);
+
+    if !is_admin() {
+        entry.set_sensitive(false);
+        return bar;
+    }
+    entry.set_tooltip_text(Some("Press Enter to manage this user's fingerprints"));
+    disable_while_busy(state, &entry);
 
-    dialog.connect_response(move |dialog, response| {
-        if response == gtk4::ResponseType::Cancel {
-            dialog.destroy();
+    let window_weak = window.downgrade();
+    entry.connect_activate(move |entry| {
+        let username = entry.text().trim().to_string();
+        if username.is_empty() || username == dbus::target_user() {
+            return;
+        }
+        dbus::set_target_user(username);
+        if let Some(window) = window_weak.upgrade() {
+            let _ = window.activate_action("win.refresh", None);
         }
     });
 
-    dialog.show();
+    bar
+}
 
-    // Start enrollment in a separate thread to not block the UI
-    let _dialog_weak = dialog.downgrade();
-    let _window_weak = window.downgrade();
-    let sender = sender.clone();
-    glib::spawn_future_local(async move {
-        proxy.claim(&whoami::username()).await.unwrap();
-        let _ = proxy.enroll_start(&finger_name.as_str()).await;
-        let mut enroll_status_stream = proxy.receive_enroll_status().await.unwrap();
+/// How long to wait for another application to release the device.
+const CLAIM_WAIT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Claims the device, offering to wait while another application has it claimed.
+///
+/// The wait shows a dialog with its progress and a Cancel button on `parent`.
+async fn claim_device_or_wait(
+    parent: &impl IsA<gtk4::Window>,
+    proxy: &FPrintDeviceProxy<'static>,
+    cancel: &CancellationToken,
+) -> Result<(), AppError> {
+    let claim_proxy = proxy.clone();
+    let claimed =
+        dbus_off_main_thread(async move { Ok(claim_proxy.claim(&dbus::target_user()).await) })
+            .await?;
+    match claimed {
+        Ok(()) => return Ok(()),
+        Err(e) if dbus::is_device_busy(&e) => {}
+        Err(e) => return Err(AppError::dbus("calling FPrint.Device.Claim", e)),
+    }
+
+    let progress = gtk4::ProgressBar::new();
+    let dialog = adw::MessageDialog::new(
+        Some(parent),
+        Some("Waiting for Fingerprint Device"),
+        Some("Another application is using the fingerprint reader."),
+    );
+    dialog.set_extra_child(Some(&progress));
+    dialog.add_response("cancel", "_Cancel");
+    dialog.set_close_response("cancel");
+    let cancel = cancel.child_token();
+    let cancel_on_close = cancel.clone();
+    dialog.connect_response(None, move |_, _| cancel_on_close.cancel());
+    dialog.present();
+
+    let started = std::time::Instant::now();
+    let total = CLAIM_WAIT_TIMEOUT.as_secs();
+    let dialog_weak = dialog.downgrade();
+    let ticker = glib::timeout_add_seconds_local(1, move || {
+        let Some(dialog) = dialog_weak.upgrade() else {
+            return ControlFlow::Break;
+        };
+        let waited = started.elapsed().as_secs().min(total);
+        dialog.set_body(&format!(
+            "Another application is using the fingerprint reader. Waiting {waited}s / {total}s…"
+        ));
+        progress.set_fraction(waited as f64 / total as f64);
+        ControlFlow::Continue
+    });
 
-        let result = loop {if let Some(msg) = enroll_status_stream.next().await {
-            // struct `JobNewArgs` is generated from `job_new` signal function arguments
-            let args = msg.args().expect("Error parsing message");
+    let wait_proxy = proxy.clone();
+    let outcome = dbus_off_main_thread(async move {
+        Ok(dbus::await_device_claim_release(&wait_proxy, CLAIM_WAIT_TIMEOUT, &cancel).await)
+    })
+    .await;
+    ticker.remove();
+    dialog.close();
+    match outcome?.map_err(|e| AppError::dbus("calling FPrint.Device.Claim", e))? {
+        dbus::ClaimWait::Claimed => Ok(()),
+        dbus::ClaimWait::TimedOut => Err(AppError::DeviceBusy),
+        dbus::ClaimWait::Cancelled => Err(AppError::Cancelled),
+    }
+}
 
-            if !args.done {
-                continue;
-            }
+/// How long verification waits for a finger before giving up.
+const VERIFY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// The finger name fprintd's `VerifyStart` takes to match against every enrolled finger.
+const VERIFY_ANY: &str = "any";
+
+async fn handle_verification(
+    state: &Rc<AppState>,
+    window: &ApplicationWindow,
+    finger_name: String,
+) -> Result<(), AppError> {
+    let Some(operation) = state.begin_operation(Operation::Verify) else {
+        return Ok(());
+    };
+    let proxy = dbus_off_main_thread(async {
+        let conn = dbus::connect()
+            .await
+            .map_err(|e| AppError::dbus("connecting to the system bus", e))?;
+        dbus::device_proxy(&conn)
+            .await
+            .map_err(|e| AppError::dbus("creating the FPrint.Device proxy", e))
+    })
+    .await?;
+    claim_device_or_wait(window, &proxy, operation.cancel_token()).await?;
+
+    let dialog = adw::MessageDialog::new(
+        Some(window),
+        Some("Place your finger on the sensor to verify"),
+        None,
+    );
+    dialog.add_response("cancel", "_Cancel");
+    dialog.set_close_response("cancel");
+    let cancel = operation.cancel_token().child_token();
+    let cancel_on_close = cancel.clone();
+    dialog.connect_response(None, move |_, _| cancel_on_close.cancel());
+
+    dialog.present();
+
+    glib::g_debug!(
+        LOG_DOMAIN,
+        "Starting verification for finger: {}",
+        finger_name
+    );
+    let verify_finger = finger_name.clone();
+    let result = run_off_main_thread(async move {
+        let result = dbus::get_verification_result_with_timeout(
+            &proxy,
+            &verify_finger,
+            VERIFY_TIMEOUT,
+            &cancel,
+        )
+        .await;
+        let _ = proxy.verify_stop().await;
+        if let Err(e) = dbus::release_if_claimed(&proxy).await {
+            glib::g_warning!(LOG_DOMAIN, "Failed to release fingerprint device: {}", e);
+        }
+        Ok(result)
+    })
+    .await
+    .unwrap_or_else(|e| VerifyResult::Error(e.to_string()));
+    operation.set_succeeded(result == VerifyResult::Match);
+    drop(operation);
+    let finger = FingerName::try_from(finger_name.as_str()).ok();
+    let (kind, outcome) = match &result {
+        VerifyResult::Match => (ActivityKind::Success, "matched".to_string()),
+        VerifyResult::NoMatch => (ActivityKind::Failure, "did not match".to_string()),
+        VerifyResult::TimedOut => (ActivityKind::Failure, "timed out".to_string()),
+        VerifyResult::Cancelled => (ActivityKind::Hint, "was cancelled".to_string()),
+        VerifyResult::Error(e) => (ActivityKind::Failure, format!("failed: {e}")),
+    };
+    let subject = finger.map_or("Any finger", |finger| finger.display_name());
+    state.activity.log(kind, &format!("{subject} {outcome}"));
+    // Verifying any finger doesn't say which one matched, so there's nothing to record
+    if let Some(finger) = finger {
+        history::record(finger, history::HistoryEvent::Verified(result.clone()));
+    }
+
+    dialog.close();
+    match result {
+        VerifyResult::Cancelled => {}
+        result => {
+            let success = result == VerifyResult::Match;
+            let toast: adw::Toast = result.into();
+            toast.set_action_target_value(Some(&finger_name.to_variant()));
+            show_toast(window, toast);
+            let _ = window.activate_action("win.feedback", Some(&success.to_variant()));
+        }
+    }
+
+    Ok(())
+}
+
+fn enroll_status_hint(result: &str) -> &'static str {
+    match result {
+        "enroll-stage-passed" => "Good scan. Lift your finger and place it again",
+        "enroll-retry-scan" => "Scan failed, please try again",
+        "enroll-swipe-too-short" => "Swipe was too short, please try again",
+        "enroll-finger-not-centered" => "Finger not centered, please try again",
+        "enroll-remove-and-retry" => "Remove your finger and try again",
+        "enroll-completed" => "Enrollment complete",
+        _ => "",
+    }
+}
+
+fn update_stage_progress(stage_bar: &gtk4::LevelBar, current_stage: u32, total_stages: u32) {
+    stage_bar.set_value(current_stage as f64);
+    stage_bar.update_property(&[
+        gtk4::accessible::Property::ValueNow(current_stage as f64),
+        gtk4::accessible::Property::ValueMin(0.0),
+        gtk4::accessible::Property::ValueMax(total_stages as f64),
+        gtk4::accessible::Property::ValueText(&format!("{current_stage} of {total_stages} stages")),
+    ]);
+}
 
+/// Rough time needed for the given number of stages, based on typical fprintd behavior.
+fn estimate_enrollment_time(num_stages: u32, scan_type: ScanType) -> std::time::Duration {
+    let seconds_per_stage = match scan_type {
+        ScanType::Swipe => 8,
+        ScanType::Press => 4,
+    };
+    std::time::Duration::from_secs(u64::from(num_stages * seconds_per_stage))
+}
 
+/// Widgets on the wizard's enrollment step that `run_enrollment` keeps up to date.
+struct EnrollProgressView {
+    stage_bar: gtk4::LevelBar,
+    hint_label: Label,
+    estimate_label: Label,
+    cancel_button: Button,
+    activity: activity::ActivityLog,
+}
 
-            match dbg!(args.result.as_str()) {
+impl EnrollProgressView {
+    fn update_estimate(&self, remaining_stages: u32, scan_type: ScanType) {
+        let estimate = estimate_enrollment_time(remaining_stages, scan_type);
+        self.estimate_label.set_text(&format!(
+            "Estimated: ~{} seconds remaining.",
+            estimate.as_secs()
+        ));
+    }
+}
 
-                "enroll-completed" => {
-                    break Ok(());
-                },
-                "enroll-stage-passed" |
-                "enroll-retry-scan" |
-                "enroll-swipe-too-short" |
-                "enroll-finger-not-centered" |
-                "enroll-remove-and-retry" => continue,
-                _ => {
-                    break Err(args.result);
+/// How long enrollment waits for any status, retries included, before assuming the sensor
+/// stalled. Unlike an overall timeout this doesn't grow with the number of stages.
+const ENROLL_STALL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Claims the device and runs one enrollment session, reporting progress on `view`.
+async fn run_enrollment(
+    finger_name: &str,
+    view: &EnrollProgressView,
+    cancel: &CancellationToken,
+) -> anyhow::Result<EnrollResult> {
+    glib::g_debug!(
+        LOG_DOMAIN,
+        "Starting enrollment for finger: {}",
+        finger_name
+    );
+    let conn = dbus::connect()
+        .await
+        .context("connecting to the system bus")?;
+    let proxy = dbus::device_proxy(&conn)
+        .await
+        .context("creating the FPrint.Device proxy")?;
+
+    // fprintd reports -1 when the number of stages is unknown
+    let num_stages = proxy.num_enroll_stages().await.unwrap_or(-1).max(0) as u32;
+    let scan_type = ScanType::from_property(&proxy.scan_type().await.unwrap_or_default());
+    view.stage_bar.set_max_value(num_stages.max(1) as f64);
+    view.stage_bar.set_visible(num_stages > 0);
+    view.estimate_label.set_visible(num_stages > 0);
+    update_stage_progress(&view.stage_bar, 0, num_stages);
+    view.update_estimate(num_stages, scan_type);
+    view.hint_label.set_text("");
+
+    match view.stage_bar.root().and_downcast::<gtk4::Window>() {
+        Some(window) => claim_device_or_wait(&window, &proxy, cancel).await?,
+        None => proxy
+            .claim(&dbus::target_user())
+            .await
+            .context("calling FPrint.Device.Claim")?,
+    }
+    // Cancelling ends the session like any other result, so it is still stopped and the
+    // device released
+    let cancel = cancel.child_token();
+    let cancel_on_click = cancel.clone();
+    let cancel_handler = view
+        .cancel_button
+        .connect_clicked(move |_| cancel_on_click.cancel());
+    view.cancel_button.set_sensitive(true);
+
+    let mut current_stage = 0;
+    let result = dbus::run_enroll_session(
+        &proxy,
+        finger_name,
+        ENROLL_STALL_TIMEOUT,
+        &cancel,
+        |status| {
+            if status == "enroll-stage-passed" {
+                current_stage += 1;
+                update_stage_progress(&view.stage_bar, current_stage, num_stages);
+                view.update_estimate(num_stages.saturating_sub(current_stage), scan_type);
+                if let Ok(finger) = FingerName::try_from(finger_name) {
+                    settings::record_enrollment_stage(finger, current_stage);
                 }
             }
-        }};
-
-        let _ = proxy.enroll_stop().await;
-
-                // "verify-match" => {
-                //     break Ok(());
-                // },
-                // "verify-retry-scan" |
-                // "verify-swipe-too-short" |
-                // "verify-finger-not-centered" |
-                // "verify-remove-and-retry" => continue,
-                // _ => {
-                //     break Err(args.result);
-        // }
-        let _ = proxy.release().await;
-        let _ = sender.send(result).await; // Send result back to main thread
-        drop(proxy);
-    });
-
-    // Set up a recurring check for messages
-    let dialog_weak2 = dialog.downgrade();
-    let window_weak2 = window.downgrade();
-    glib::timeout_add_local(std::time::Duration::from_millis(100), move || {
-        let receiver = receiver.clone();
-        let dialog_weak = dialog_weak2.clone();
-        let window_weak = window_weak2.clone();
+            let hint = enroll_status_hint(status);
+            view.hint_label.set_text(hint);
+            if !hint.is_empty() && status != "enroll-completed" {
+                view.activity.log(ActivityKind::Hint, hint);
+            }
+        },
+    )
+    .await;
+
+    view.cancel_button.disconnect(cancel_handler);
+    view.cancel_button.set_sensitive(false);
+    let result = result?;
+    glib::g_info!(
+        LOG_DOMAIN,
+        "Enrollment of {} finished: {:?}",
+        finger_name,
+        result
+    );
+    if result == EnrollResult::Completed {
+        settings::clear_enrollment_stage();
+    }
+
+    Ok(result)
+}
+
+/// Outcome of [`reenroll_finger_flow`].
+enum ReenrollOutcome {
+    /// The old print couldn't be deleted, so nothing changed.
+    DeleteFailed(anyhow::Error),
+    /// The old print is gone; this is how enrolling the new one went.
+    Enrolled(anyhow::Result<EnrollResult>),
+}
+
+/// Replaces `finger`'s print: deletes the old one without asking, since the user already
+/// confirmed replacing it, then enrolls the finger again.
+async fn reenroll_finger_flow(
+    state: &AppState,
+    finger: &FingerName,
+    view: &EnrollProgressView,
+    cancel: &CancellationToken,
+) -> ReenrollOutcome {
+    if let Err(e) = run_off_main_thread(delete_enrolled_finger(finger.as_str())).await {
+        return ReenrollOutcome::DeleteFailed(e);
+    }
+    state.forget_enrolled_finger(finger);
+    ReenrollOutcome::Enrolled(run_enrollment(finger.as_str(), view, cancel).await)
+}
+
+/// Off-screen carousel steps stay in the widget tree, so keeps Tab from wandering into them,
+/// and hands focus to the first control of the step that just came into view.
+fn focus_current_step_only(carousel: &adw::Carousel) {
+    for position in 1..carousel.n_pages() {
+        carousel.nth_page(position).set_can_focus(false);
+    }
+    carousel.connect_page_changed(|carousel, index| {
+        for position in 0..carousel.n_pages() {
+            carousel.nth_page(position).set_can_focus(position == index);
+        }
+        carousel
+            .nth_page(index)
+            .child_focus(gtk4::DirectionType::TabForward);
+    });
+}
+
+/// Builds the three-step enrollment wizard: choose a finger, enroll it, see the result.
+fn create_enroll_wizard(
+    state: &Rc<AppState>,
+    window: &ApplicationWindow,
+    finger_label: &Label,
+    finger_selector: &gtk4::DropDown,
+    nav: &adw::NavigationView,
+) -> GBox {
+    let wizard = GBox::new(Orientation::Vertical, 10);
+
+    // Steps are changed with the buttons only, so the user can't swipe away from a running
+    // enrollment
+    let carousel = adw::Carousel::builder()
+        .interactive(false)
+        .vexpand(true)
+        .build();
+    let dots = adw::CarouselIndicatorDots::builder()
+        .carousel(&carousel)
+        .build();
+
+    // Step 1: Choose finger
+    let choose_page = GBox::new(Orientation::Vertical, 10);
+    choose_page.set_hexpand(true);
+    let start_button = Button::with_label("Enroll");
+    start_button.add_css_class("suggested-action");
+    choose_page.append(finger_label);
+    choose_page.append(&create_finger_selector_squeezer(finger_selector));
+    choose_page.append(&start_button);
+    disable_while_busy(state, &choose_page);
+
+    // Step 2: Enroll
+    let progress_page = GBox::new(Orientation::Vertical, 10);
+    progress_page.set_hexpand(true);
+    progress_page.set_valign(gtk4::Align::Center);
+    let progress_title = Label::new(Some("Place your finger on the sensor"));
+    progress_title.add_css_class("title-2");
+    // A LevelBar is a meter to assistive technologies; this one tracks progress
+    let stage_bar = gtk4::LevelBar::builder()
+        .min_value(0.0)
+        .max_value(1.0)
+        .mode(gtk4::LevelBarMode::Discrete)
+        .accessible_role(gtk4::AccessibleRole::ProgressBar)
+        .build();
+
+    // The Log role makes screen readers announce new hints as they arrive
+    let hint_box = GBox::builder()
+        .orientation(Orientation::Vertical)
+        .accessible_role(gtk4::AccessibleRole::Log)
+        .build();
+    // A live region, so screen readers announce each new hint without the user moving there
+    let hint_label = Label::builder()
+        .accessible_role(gtk4::AccessibleRole::Status)
+        .build();
+    hint_label.update_property(&[gtk4::accessible::Property::Label("Enrollment hint")]);
+    hint_box.append(&hint_label);
+
+    // Best-effort guidance only, not a timer
+    let estimate_label = Label::new(None);
+    estimate_label.add_css_class("dim-label");
+
+    // Only sensitive while an enrollment session is running
+    let cancel_button = Button::with_label("Cancel");
+    cancel_button.set_halign(gtk4::Align::Center);
+    cancel_button.set_sensitive(false);
+
+    progress_page.append(&progress_title);
+    progress_page.append(&stage_bar);
+    progress_page.append(&hint_box);
+    progress_page.append(&estimate_label);
+    progress_page.append(&cancel_button);
+
+    let progress_view = Rc::new(EnrollProgressView {
+        stage_bar,
+        hint_label,
+        estimate_label,
+        cancel_button,
+        activity: state.activity.clone(),
+    });
+
+    // Step 3: Complete, shown in place rather than in a dialog
+    let complete_page = adw::StatusPage::builder().hexpand(true).build();
+    let (error_details, error_details_label) = create_error_details();
+    error_details.set_visible(false);
+    let another_button = Button::with_label("Enroll Another Finger");
+    let done_button = Button::with_label("Done");
+    done_button.add_css_class("suggested-action");
+    let complete_buttons = GBox::new(Orientation::Horizontal, 10);
+    complete_buttons.set_halign(gtk4::Align::Center);
+    complete_buttons.append(&another_button);
+    complete_buttons.append(&done_button);
+    let complete_content = GBox::new(Orientation::Vertical, 10);
+    complete_content.append(&error_details);
+    complete_content.append(&complete_buttons);
+    complete_page.set_child(Some(&complete_content));
+
+    carousel.append(&choose_page);
+    carousel.append(&progress_page);
+    carousel.append(&complete_page);
+
+    focus_current_step_only(&carousel);
+
+    wizard.append(&carousel);
+    wizard.append(&dots);
+
+    let carousel_weak = carousel.downgrade();
+    let first_page = choose_page.clone();
+    another_button.connect_clicked(move |_| {
+        if let Some(carousel) = carousel_weak.upgrade() {
+            carousel.scroll_to(&first_page, true);
+        }
+    });
+
+    // Done leaves the wizard on its first step for next time
+    let carousel_weak = carousel.downgrade();
+    let first_page = choose_page.clone();
+    let nav_weak = nav.downgrade();
+    done_button.connect_clicked(move |_| {
+        if let Some(carousel) = carousel_weak.upgrade() {
+            carousel.scroll_to(&first_page, false);
+        }
+        if let Some(nav) = nav_weak.upgrade() {
+            show_page(&nav, "main");
+        }
+    });
+
+    // Enter on the result step starts over, like an OK button would. While an enrollment is
+    // running it is swallowed, so a stray key press can't trigger anything
+    let key_controller = gtk4::EventControllerKey::new();
+    key_controller.set_propagation_phase(gtk4::PropagationPhase::Capture);
+    let key_state = state.clone();
+    let carousel_weak = carousel.downgrade();
+    let first_page = choose_page.clone();
+    key_controller.connect_key_pressed(move |_, key, _, _| {
+        if !matches!(key, gdk::Key::Return | gdk::Key::KP_Enter) {
+            return glib::Propagation::Proceed;
+        }
+        if key_state.active_operation.get().is_some() {
+            return glib::Propagation::Stop;
+        }
+        let Some(carousel) = carousel_weak.upgrade() else {
+            return glib::Propagation::Proceed;
+        };
+        let on_result_step = carousel.position().round() as u32 + 1 == carousel.n_pages();
+        if on_result_step {
+            carousel.scroll_to(&first_page, true);
+            glib::Propagation::Stop
+        } else {
+            glib::Propagation::Proceed
+        }
+    });
+    wizard.add_controller(key_controller);
 
+    let window_weak = window.downgrade();
+    let carousel_weak = carousel.downgrade();
+    let nav_weak = nav.downgrade();
+    let finger_selector = finger_selector.clone();
+    let state = state.clone();
+    start_button.connect_clicked(move |_| {
+        let (Some(window), Some(carousel)) = (window_weak.upgrade(), carousel_weak.upgrade())
+        else {
+            return;
+        };
+        let Some(finger) = selected_finger(&finger_selector) else {
+            return;
+        };
+        let Some(operation) = state.begin_operation(Operation::Enroll) else {
+            return;
+        };
+
+        let state = state.clone();
+        let choose_page = choose_page.clone();
+        let progress_page = progress_page.clone();
+        let progress_view = progress_view.clone();
+        let error_details = error_details.clone();
+        let error_details_label = error_details_label.clone();
+        let complete_page = complete_page.clone();
+        let nav_weak = nav_weak.clone();
         glib::spawn_future_local(async move {
-            let Ok(result) = receiver.try_recv() else {
-                return ControlFlow::Continue; // Keep checking for messages
-            };
-            if let Some(dialog) = dialog_weak.upgrade() {
-                dialog.destroy();
-                if let Some(window) = window_weak.upgrade() {
-                    match result {
-                        Ok(_) => {
-                            let success_dialog = gtk4::MessageDialog::new(
-                                Some(&window),
-                                gtk4::DialogFlags::MODAL,
-                                gtk4::MessageType::Info,
-                                gtk4::ButtonsType::Ok,
-                                "Enrollment successful!",
-                            );
-                            success_dialog.show();
-                        }
-                        Err(e) => {
-                            let error_dialog = gtk4::MessageDialog::new(
-                                Some(&window),
-                                gtk4::DialogFlags::MODAL,
-                                gtk4::MessageType::Error,
-                                gtk4::ButtonsType::Ok,
-                                &format!("Enrollment failed: {}", e),
-                            );
-                            error_dialog.show();
-                        }
+            let replacing = check_already_enrolled(finger).await;
+            if replacing {
+                let dialog = dialogs::already_enrolled_dialog(&finger, &window);
+                if dialog.choose_future().await != "replace" {
+                    return;
+                }
+            }
+            carousel.scroll_to(&progress_page, true);
+
+            // Enrollment doesn't wait for the portal, but releasing does, so a request answered
+            // late can't outlive the enrollment
+            let background = glib::spawn_future_local(portal::request_background(
+                "Fingerprint enrollment in progress",
+            ));
+            let result = if replacing {
+                match reenroll_finger_flow(
+                    &state,
+                    &finger,
+                    &progress_view,
+                    operation.cancel_token(),
+                )
+                .await
+                {
+                    ReenrollOutcome::DeleteFailed(e) => {
+                        let _ = background.await;
+                        portal::release_background().await;
+                        carousel.scroll_to(&choose_page, true);
+                        show_error_dialog(
+                            &window,
+                            &format!("Couldn't delete the existing fingerprint: {e:#}"),
+                        );
+                        report_connection_lost(&window, &e);
+                        return;
                     }
+                    ReenrollOutcome::Enrolled(result) => result,
                 }
+            } else {
+                run_enrollment(finger.as_str(), &progress_view, operation.cancel_token()).await
+            };
+            let _ = background.await;
+            portal::release_background().await;
+            if let Err(e) = &result {
+                report_connection_lost(&window, e);
+            }
+            let success = matches!(result, Ok(EnrollResult::Completed));
+            if let Ok(enroll_result) = &result {
+                history::record(
+                    finger,
+                    history::HistoryEvent::Enrolled(enroll_result.clone()),
+                );
+            }
+            // Stopped on purpose, so there's nothing to report or offer to retry. A cancelled
+            // re-enrollment still has to say the old print is gone, below.
+            if !replacing && matches!(result, Ok(EnrollResult::Cancelled)) {
+                settings::clear_enrollment_stage();
+                state.activity.log(
+                    ActivityKind::Hint,
+                    &format!("Enrolling the {} was cancelled", finger.display_name()),
+                );
+                carousel.scroll_to(&choose_page, true);
+                return;
+            }
+            if success {
+                state.activity.log(
+                    ActivityKind::Success,
+                    &format!("Enrolled the {}", finger.display_name()),
+                );
+            } else {
+                state.activity.log(
+                    ActivityKind::Failure,
+                    &format!("Enrolling the {} failed", finger.display_name()),
+                );
+            }
+            let toast_result = result.as_ref().ok().cloned();
+            let details = match result {
+                Ok(EnrollResult::Completed | EnrollResult::Cancelled) => None,
+                Ok(EnrollResult::Error(e)) => Some(format!("fprintd status: {e}")),
+                Err(e) => Some(format!("{e:?}")),
+            };
+            if success {
+                complete_page.set_icon_name(Some("emblem-ok-symbolic"));
+                complete_page.set_title("Enrollment Successful");
+                complete_page.set_description(Some(finger.display_name()));
+                complete_page.remove_css_class("enroll-failed");
+                complete_page.add_css_class("enroll-succeeded");
+            } else {
+                complete_page.set_icon_name(Some("dialog-error-symbolic"));
+                complete_page.set_title("Enrollment Failed");
+                complete_page.set_description(Some(if replacing {
+                    "The old fingerprint was already deleted, so this finger is no longer \
+                     enrolled."
+                } else {
+                    finger.display_name()
+                }));
+                complete_page.remove_css_class("enroll-succeeded");
+                complete_page.add_css_class("enroll-failed");
+            }
+            error_details_label.set_text(details.as_deref().unwrap_or_default());
+            error_details.set_expanded(false);
+            error_details.set_visible(details.is_some());
+            carousel.scroll_to(&complete_page, true);
+            // The result page can't be seen from elsewhere, so say it with a toast too
+            let away = nav_weak
+                .upgrade()
+                .is_some_and(|nav| visible_page_tag(&nav).as_deref() != Some("enroll"));
+            if let Some(toast_result) = toast_result.filter(|_| away) {
+                let toast: adw::Toast = toast_result.into();
+                toast.set_action_target_value(Some(&finger.as_str().to_variant()));
+                show_toast(&window, toast);
+            }
+            // Enrollment takes a while, so the user may well have switched to another window
+            if !window.is_active() {
+                let title = if success {
+                    "Enrollment Successful"
+                } else {
+                    "Enrollment Failed"
+                };
+                glib::spawn_future_local(notification::send_notification(
+                    title,
+                    finger.display_name(),
+                ));
             }
-            ControlFlow::Break // Stop the timeout after receiving the message
+            operation.set_succeeded(success);
+            drop(operation);
+            let _ = window.activate_action("win.feedback", Some(&success.to_variant()));
         });
-
-        ControlFlow::Continue
     });
 
-    Ok(())
+    wizard
+}
+
+/// Tag of the page the navigation view currently shows.
+fn visible_page_tag(nav: &adw::NavigationView) -> Option<glib::GString> {
+    nav.visible_page().and_then(|page| page.tag())
+}
+
+/// Shows a page by tag. Section pages sit directly on top of the main page, so Back always
+/// leads to the fingerprint list.
+fn show_page(nav: &adw::NavigationView, tag: &str) {
+    if visible_page_tag(nav).as_deref() == Some(tag) || nav.find_page(tag).is_none() {
+        return;
+    }
+    nav.pop_to_tag("main");
+    if tag != "main" {
+        nav.push_by_tag(tag);
+    }
 }
 
-fn create_page_content(title: &str, window: &ApplicationWindow, stack: &Stack) -> GBox {
+fn create_page_content(
+    title: &str,
+    state: &Rc<AppState>,
+    window: &ApplicationWindow,
+    nav: &adw::NavigationView,
+) -> GBox {
     let page = GBox::new(Orientation::Vertical, 10);
     page.set_margin_start(10);
     page.set_margin_end(10);
     page.set_margin_top(10);
     page.set_margin_bottom(10);
 
-    if title != "Main Menu" {
-        let header = GBox::new(Orientation::Horizontal, 10);
-        let back_button = Button::with_label("Back");
-        let title_label = Label::new(Some(title));
-        header.append(&back_button);
-        header.append(&title_label);
-        page.append(&header);
-
-        let stack_weak = stack.downgrade();
-        back_button.connect_clicked(move |_| {
-            if let Some(stack) = stack_weak.upgrade() {
-                stack.set_visible_child_name("main");
-            }
-        });
-    }
-
     if title != "Main Menu" {
         let finger_label = Label::new(Some("Select finger:"));
         let finger_selector = create_finger_selector();
-        page.append(&finger_label);
-        page.append(&finger_selector);
-
+        // The enrollment wizard places the selector in its first step, and deletion works
+        // on a list of enrolled fingers instead
         match title {
             "Enroll Fingerprint" => {
-                let enroll_button = Button::with_label("Enroll");
-                let window_weak = window.downgrade();
-                enroll_button.connect_clicked(move |_| {
-                    if let Some(window) = window_weak.upgrade() {
-                        if let Some(finger) = finger_selector.active_text() {
-                            let finger_str = finger.to_string();
-                            glib::spawn_future_local(async move {
-                                if let Err(e) = handle_enrollment(&window, finger_str).await {
-                                    let error_dialog = gtk4::MessageDialog::new(
-                                        Some(&window),
-                                        gtk4::DialogFlags::MODAL,
-                                        gtk4::MessageType::Error,
-                                        gtk4::ButtonsType::Ok,
-                                        &format!("Error: {}", e),
-                                    );
-                                    error_dialog.connect_response(|dialog, _| {
-                                        dialog.destroy();
-                                    });
-                                    error_dialog.show();
-                                }
-                            });
-                        }
+                page.append(&create_enroll_wizard(
+                    state,
+                    window,
+                    &finger_label,
+                    &finger_selector,
+                    nav,
+                ));
+
+                // Lets other parts of the UI jump straight into enrolling a given finger
+                let enroll_finger_action =
+                    gio::SimpleAction::new("enroll-finger", Some(glib::VariantTy::STRING));
+                let nav_weak = nav.downgrade();
+                enroll_finger_action.connect_activate(move |_, parameter| {
+                    let Some(finger_name) = parameter.and_then(|p| p.get::<String>()) else {
+                        return;
+                    };
+                    if let Ok(finger) = FingerName::try_from(finger_name) {
+                        select_finger(&finger_selector, finger);
+                    }
+                    if let Some(nav) = nav_weak.upgrade() {
+                        show_page(&nav, "enroll");
                     }
                 });
-                page.append(&enroll_button);
+                window.add_action(&enroll_finger_action);
             }
             "Verify Fingerprint" => {
+                let any_finger_check = gtk4::CheckButton::with_label("Verify any finger");
+                let squeezer = create_finger_selector_squeezer(&finger_selector);
+                // The box follows busy state and the squeezer the check button, so neither
+                // re-enables the selector while the other wants it off
+                let selector_box = GBox::new(Orientation::Vertical, 0);
+                selector_box.append(&squeezer);
+                disable_while_busy(state, &selector_box);
+                disable_while_busy(state, &any_finger_check);
+                any_finger_check
+                    .bind_property("active", &squeezer, "sensitive")
+                    .invert_boolean()
+                    .sync_create()
+                    .build();
+                page.append(&finger_label);
+                page.append(&selector_box);
+                page.append(&any_finger_check);
+
+                // Preselect the most likely enrolled finger the first time the page is shown
+                let suggested = Rc::new(std::cell::Cell::new(false));
+                let suggest_state = state.clone();
+                let selector = finger_selector.clone();
+                let suggested_flag = suggested.clone();
+                nav.connect_visible_page_notify(move |nav| {
+                    let suggested = &suggested_flag;
+                    if visible_page_tag(nav).as_deref() != Some("verify") || suggested.replace(true)
+                    {
+                        return;
+                    }
+                    let state = suggest_state.clone();
+                    let selector = selector.clone();
+                    glib::spawn_future_local(async move {
+                        if let Some(finger) = auto_detect_best_finger(&state).await {
+                            select_finger(&selector, finger);
+                        }
+                    });
+                });
+
+                // Opens this page with a given finger selected, e.g. from an enrolled finger row
+                let show_verify_action =
+                    gio::SimpleAction::new("show-verify", Some(glib::VariantTy::STRING));
+                let selector = finger_selector.clone();
+                let any_check = any_finger_check.clone();
+                let nav_weak = nav.downgrade();
+                show_verify_action.connect_activate(move |_, parameter| {
+                    let Some(finger) = parameter
+                        .and_then(|p| p.get::<String>())
+                        .and_then(|name| FingerName::try_from(name).ok())
+                    else {
+                        return;
+                    };
+                    // An explicit choice wins over the first-show suggestion
+                    suggested.set(true);
+                    any_check.set_active(false);
+                    select_finger(&selector, finger);
+                    if let Some(nav) = nav_weak.upgrade() {
+                        show_page(&nav, "verify");
+                    }
+                });
+                window.add_action(&show_verify_action);
+
+                // Verifies a given finger directly, e.g. from a toast's Retry button
+                let verify_finger_action =
+                    gio::SimpleAction::new("verify-finger", Some(glib::VariantTy::STRING));
+                let verify_state = state.clone();
+                let window_weak = window.downgrade();
+                verify_finger_action.connect_activate(move |_, parameter| {
+                    let Some(finger_name) = parameter.and_then(|p| p.get::<String>()) else {
+                        return;
+                    };
+                    let Some(window) = window_weak.upgrade() else {
+                        return;
+                    };
+                    let state = verify_state.clone();
+                    glib::spawn_future_local(async move {
+                        if let Err(e) = handle_verification(&state, &window, finger_name).await {
+                            show_app_error(&window, &e);
+                        }
+                    });
+                });
+                window.add_action(&verify_finger_action);
+
                 let verify_button = Button::with_label("Verify");
+                disable_while_busy(state, &verify_button);
+                let state = state.clone();
                 let window_weak = window.downgrade();
                 verify_button.connect_clicked(move |_| {
                     if let Some(window) = window_weak.upgrade() {
-                        let Some(finger_name) = finger_selector.active_text().map(String::from)
-                        else {
+                        let state = state.clone();
+                        if any_finger_check.is_active() {
+                            glib::spawn_future_local(async move {
+                                let finger_name = VERIFY_ANY.to_string();
+                                if let Err(e) =
+                                    handle_verification(&state, &window, finger_name).await
+                                {
+                                    show_app_error(&window, &e);
+                                }
+                            });
+                            return;
+                        }
+                        let Some(finger) = selected_finger(&finger_selector) else {
                             return;
                         };
+                        let finger_name = finger.as_str().to_string();
                         glib::spawn_future_local(async move {
-                            if let Err(e) = handle_verification(&window, finger_name).await {
-                                let error_dialog = gtk4::MessageDialog::new(
-                                    Some(&window),
-                                    gtk4::DialogFlags::MODAL,
-                                    gtk4::MessageType::Error,
-                                    gtk4::ButtonsType::Ok,
-                                    &format!("Error: {}", e),
-                                );
-                                error_dialog.connect_response(|dialog, _| {
-                                    dialog.destroy();
-                                });
-                                error_dialog.show();
+                            match check_finger_enrolled(&finger_name).await {
+                                Ok(()) => {}
+                                Err(AppError::FingerNotEnrolled(finger)) => {
+                                    offer_enrollment(&window, finger);
+                                    return;
+                                }
+                                Err(e) => {
+                                    show_app_error(&window, &e);
+                                    return;
+                                }
+                            }
+                            if let Err(e) = handle_verification(&state, &window, finger_name).await
+                            {
+                                show_app_error(&window, &e);
                             }
                         });
                     }
                 });
                 page.append(&verify_button);
             }
-            "List Fingerprints" => {
-                let list_button = Button::with_label("List");
-                page.append(&list_button);
-            }
             "Delete Fingerprint" => {
-                let delete_button = Button::with_label("Delete");
+                let fingers_list = ListBox::new();
+                fingers_list.set_selection_mode(SelectionMode::None);
+                fingers_list.add_css_class("boxed-list");
+                let delete_button = Button::with_label("Delete Selected");
+                delete_button.add_css_class("destructive-action");
+                page.append(&fingers_list);
                 page.append(&delete_button);
+                disable_while_busy(state, &fingers_list);
+                disable_while_busy(state, &delete_button);
+
+                let selection = FingerChecks::default();
+
+                // Re-read the enrolled fingers every time the page is shown
+                let list_weak = fingers_list.downgrade();
+                let selection_clone = selection.clone();
+                nav.connect_visible_page_notify(move |nav| {
+                    if visible_page_tag(nav).as_deref() != Some("delete") {
+                        return;
+                    }
+                    if let Some(list) = list_weak.upgrade() {
+                        fill_delete_list(&list, &selection_clone);
+                    }
+                });
+
+                let state = state.clone();
+                let window_weak = window.downgrade();
+                let list_weak = fingers_list.downgrade();
+                delete_button.connect_clicked(move |_| {
+                    let Some(window) = window_weak.upgrade() else {
+                        return;
+                    };
+                    let fingers: Vec<FingerName> = selection
+                        .borrow()
+                        .iter()
+                        .filter(|(_, check)| check.is_active())
+                        .map(|(finger, _)| *finger)
+                        .collect();
+                    if fingers.is_empty() {
+                        return;
+                    }
+
+                    let confirm_dialog = dialogs::confirm_delete_dialog(&fingers, &window);
+                    let state = state.clone();
+                    let selection = selection.clone();
+                    let list_weak = list_weak.clone();
+                    confirm_dialog.connect_response(None, move |_, response| {
+                        if response != "delete" {
+                            return;
+                        }
+                        let window = window.clone();
+                        let fingers = fingers.clone();
+                        let state = state.clone();
+                        let selection = selection.clone();
+                        let list_weak = list_weak.clone();
+                        glib::spawn_future_local(async move {
+                            delete_fingers(&state, &window, &fingers).await;
+                            if let Some(list) = list_weak.upgrade() {
+                                fill_delete_list(&list, &selection);
+                            }
+                        });
+                    });
+                    confirm_dialog.present();
+                });
             }
             _ => {}
         }
@@ -415,124 +1339,1595 @@ fn create_page_content(title: &str, window: &ApplicationWindow, stack: &Stack) -
     page
 }
 
-async fn get_enrolled_fingers() -> anyhow::Result<Vec<String>> {
-    let conn = Connection::system().await?;
-    let proxy = FPrintDeviceProxy::new(&conn).await?;
-    Ok(proxy.list_enrolled_fingers(&whoami::username()).await?)
-}
-
-fn build_ui(app: &Application) {
-    let window = ApplicationWindow::builder()
-        .application(app)
-        .title("Fingerprint Manager")
-        .default_width(400)
-        .default_height(300)
-        .build();
-
-    let stack = Stack::new();
-
-    // Create main menu
-    let main_page = create_page_content("Main Menu", &window, &stack);
-
-    let enroll_button = Button::with_label("Enroll Fingerprint");
-    let verify_button = Button::with_label("Verify Fingerprint");
-    let delete_button = Button::with_label("Delete Fingerprint");
-
-    // Add enrolled fingers list
-    let enrolled_list = Label::new(Some("Loading enrolled fingerprints..."));
-    enrolled_list.set_margin_top(20);
-
-    let stack_weak = stack.downgrade();
-    enroll_button.connect_clicked(move |_| {
-        if let Some(stack) = stack_weak.upgrade() {
-            stack.set_visible_child_name("enroll");
-        }
-    });
-
-    let stack_weak = stack.downgrade();
-    verify_button.connect_clicked(move |_| {
-        if let Some(stack) = stack_weak.upgrade() {
-            stack.set_visible_child_name("verify");
+/// Greys `widget` out while any operation in `state` is running, including one that is
+/// already running.
+fn disable_while_busy(state: &Rc<AppState>, widget: &impl IsA<gtk4::Widget>) {
+    let widget = widget.upcast_ref::<gtk4::Widget>();
+    widget.set_sensitive(state.active_operation.get().is_none());
+    let widget_weak = widget.downgrade();
+    let listener = state.connect_busy_changed(move |busy| {
+        if let Some(widget) = widget_weak.upgrade() {
+            widget.set_sensitive(!busy);
         }
     });
-
-    let stack_weak = stack.downgrade();
-    delete_button.connect_clicked(move |_| {
-        if let Some(stack) = stack_weak.upgrade() {
-            stack.set_visible_child_name("delete");
+    // Pages and rows are rebuilt over the app's lifetime, so listeners go with their widget
+    let state_weak = Rc::downgrade(state);
+    widget.connect_destroy(move |_| {
+        if let Some(state) = state_weak.upgrade() {
+            state.disconnect_busy_changed(listener);
         }
     });
+}
 
-    main_page.append(&enroll_button);
-    main_page.append(&verify_button);
-    main_page.append(&delete_button);
-    main_page.append(&enrolled_list);
-
-    // Set up enrolled fingers list update
-    let enrolled_list_weak = enrolled_list.downgrade();
+/// Calls `f` on the main thread for each event on the app's event bus, until it returns
+/// `ControlFlow::Break`.
+fn connect_app_events(state: &AppState, mut f: impl FnMut(&AppEvent) -> ControlFlow + 'static) {
+    let mut events = state.subscribe();
     glib::spawn_future_local(async move {
-        match get_enrolled_fingers().await {
-            Ok(fingers) => {
-                if let Some(label) = enrolled_list_weak.upgrade() {
-                    if fingers.is_empty() {
-                        label.set_text("No fingerprints enrolled");
-                    } else {
-                        label.set_text(&format!("Enrolled fingerprints:\n{}", fingers.join("\n")));
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if f(&event) == ControlFlow::Break {
+                        break;
                     }
                 }
-            }
-            Err(e) => {
-                if let Some(label) = enrolled_list_weak.upgrade() {
-                    label.set_text(&format!("Error loading fingerprints: {}", e));
-                }
+                // Missed a few; the next event is still worth handling
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
             }
         }
     });
+}
 
-    stack.add_named(&main_page, Some("main"));
+/// Runs D-Bus work that doesn't touch widgets on the tokio runtime and hands its result
+/// back to the caller.
+///
+/// Await this from `glib::spawn_future_local` and update widgets only after it returns:
+/// GTK objects are `!Send` and must stay on the main thread, so `future` must never
+/// capture them.
+async fn run_off_main_thread<F, T>(future: F) -> anyhow::Result<T>
+where
+    F: std::future::Future<Output = anyhow::Result<T>> + Send + 'static,
+    T: Send + 'static,
+{
+    let (sender, receiver) = async_channel::bounded(1);
+    tokio::spawn(async move {
+        let _ = sender.send(future.await).await;
+    });
+    receiver.recv().await?
+}
 
-    // Create other pages
-    let enroll_page = create_page_content("Enroll Fingerprint", &window, &stack);
-    let verify_page = create_page_content("Verify Fingerprint", &window, &stack);
-    let delete_page = create_page_content("Delete Fingerprint", &window, &stack);
+/// [`run_off_main_thread`] for D-Bus work that fails with an [`AppError`].
+async fn dbus_off_main_thread<F, T>(future: F) -> Result<T, AppError>
+where
+    F: std::future::Future<Output = Result<T, AppError>> + Send + 'static,
+    T: Send + 'static,
+{
+    match run_off_main_thread(async move { Ok(future.await) }).await {
+        Ok(result) => result,
+        // The task panicked or the runtime is shutting down, so the work never finished
+        Err(_) => Err(AppError::Cancelled),
+    }
+}
+
+async fn check_finger_enrolled(finger_name: &str) -> Result<(), AppError> {
+    let conn = dbus::connect()
+        .await
+        .map_err(|e| AppError::dbus("connecting to the system bus", e))?;
+    let proxy = dbus::device_proxy(&conn)
+        .await
+        .map_err(|e| AppError::dbus("creating the FPrint.Device proxy", e))?;
+    dbus::ensure_finger_is_enrolled(&proxy, &dbus::target_user(), finger_name).await
+}
+
+async fn check_already_enrolled(finger: FingerName) -> bool {
+    check_finger_enrolled(finger.as_str()).await.is_ok()
+}
+
+fn offer_enrollment(window: &ApplicationWindow, finger: FingerName) {
+    let dialog = adw::MessageDialog::new(
+        Some(window),
+        Some("Finger Not Enrolled"),
+        Some("This finger is not enrolled. Would you like to enroll it?"),
+    );
+    dialog.add_responses(&[("cancel", "_Cancel"), ("enroll", "_Enroll")]);
+    dialog.set_response_appearance("enroll", adw::ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("enroll"));
+    dialog.set_close_response("cancel");
+    let window_weak = window.downgrade();
+    dialog.connect_response(None, move |_, response| {
+        if response != "enroll" {
+            return;
+        }
+        if let Some(window) = window_weak.upgrade() {
+            let _ =
+                window.activate_action("win.enroll-finger", Some(&finger.as_str().to_variant()));
+        }
+    });
+    dialog.present();
+}
+
+/// The canonical names of the current user's enrolled fingers, empty if there are none.
+async fn get_enrolled_fingers() -> Result<Vec<String>, AppError> {
+    let conn = dbus::connect()
+        .await
+        .map_err(|e| AppError::dbus("connecting to the system bus", e))?;
+    let proxy = dbus::device_proxy(&conn)
+        .await
+        .map_err(|e| AppError::dbus("creating the FPrint.Device proxy", e))?;
+    dbus::enrolled_fingers(&proxy, &dbus::target_user()).await
+}
+
+/// Order in which enrolled fingers are suggested for verification.
+const FINGER_PRIORITY: [FingerName; 10] = [
+    FingerName::RightIndexFinger,
+    FingerName::LeftIndexFinger,
+    FingerName::RightMiddleFinger,
+    FingerName::LeftMiddleFinger,
+    FingerName::RightThumb,
+    FingerName::LeftThumb,
+    FingerName::RightRingFinger,
+    FingerName::LeftRingFinger,
+    FingerName::RightLittleFinger,
+    FingerName::LeftLittleFinger,
+];
+
+/// The enrolled finger the user most likely wants to verify with, or `None` if nothing is
+/// enrolled or the list can't be read.
+async fn auto_detect_best_finger(state: &AppState) -> Option<FingerName> {
+    let fingers = run_off_main_thread(async { Ok(get_enrolled_fingers().await?) })
+        .await
+        .ok()?;
+    let enrolled: HashSet<FingerName> = fingers
+        .iter()
+        .filter_map(|name| FingerName::try_from(name.as_str()).ok())
+        .collect();
+    // The user's default finger wins whenever it's enrolled
+    let best = settings::default_finger()
+        .into_iter()
+        .chain(FINGER_PRIORITY)
+        .find(|finger| enrolled.contains(finger));
+    state.set_enrolled_fingers(enrolled);
+    best
+}
+
+/// Deletes every print enrolled for the current user.
+async fn delete_all_enrolled_fingers() -> anyhow::Result<()> {
+    let conn = dbus::connect()
+        .await
+        .context("connecting to the system bus")?;
+    let proxy = dbus::device_proxy(&conn)
+        .await
+        .context("creating the FPrint.Device proxy")?;
+    proxy
+        .claim(&dbus::target_user())
+        .await
+        .context("calling FPrint.Device.Claim")?;
+    let result = match proxy.delete_enrolled_fingers2().await {
+        // Nothing to delete is as good as deleted
+        Err(e) if fprintd_error_name(&e) == Some("NoEnrolledPrints") => Ok(()),
+        result => result,
+    };
+    let released = dbus::release_if_claimed(&proxy).await;
+    result.context("calling FPrint.Device.DeleteEnrolledFingers2")?;
+    released.context("calling FPrint.Device.Release")
+}
+
+async fn delete_enrolled_finger(finger_name: &str) -> anyhow::Result<()> {
+    let conn = dbus::connect()
+        .await
+        .context("connecting to the system bus")?;
+    let proxy = dbus::device_proxy(&conn)
+        .await
+        .context("creating the FPrint.Device proxy")?;
+    proxy
+        .claim(&dbus::target_user())
+        .await
+        .context("calling FPrint.Device.Claim")?;
+    let result = proxy.delete_enrolled_finger(finger_name).await;
+    let released = dbus::release_if_claimed(&proxy).await;
+    result
+        .with_context(|| format!("calling FPrint.Device.DeleteEnrolledFinger for {finger_name}"))?;
+    released.context("calling FPrint.Device.Release")
+}
+
+/// Per-finger outcome of a batch deletion.
+type DeleteResults = Vec<(FingerName, zbus::Result<()>)>;
+
+/// Check buttons of the delete page, keyed by the finger they select.
+type FingerChecks = Rc<RefCell<Vec<(FingerName, gtk4::CheckButton)>>>;
+
+/// Deletes `fingers` one after another under a single claim.
+///
+/// A failing deletion doesn't abort the batch; every finger's outcome is reported back.
+async fn batch_delete_fingers(
+    state: &AppState,
+    window: &ApplicationWindow,
+    fingers: &[FingerName],
+    cancel: &CancellationToken,
+) -> anyhow::Result<DeleteResults> {
+    let conn = dbus::connect()
+        .await
+        .context("connecting to the system bus")?;
+    let proxy = dbus::device_proxy(&conn)
+        .await
+        .context("creating the FPrint.Device proxy")?;
+    claim_device_or_wait(window, &proxy, cancel).await?;
+
+    let mut results = Vec::with_capacity(fingers.len());
+    for &finger in fingers {
+        // Fingers already deleted stay deleted; the rest are left alone
+        if cancel.is_cancelled() {
+            break;
+        }
+        let result = proxy.delete_enrolled_finger(finger.as_str()).await;
+        if result.is_ok() {
+            state.forget_enrolled_finger(&finger);
+        }
+        results.push((finger, result));
+    }
+
+    if let Err(e) = dbus::release_if_claimed(&proxy).await {
+        glib::g_warning!(LOG_DOMAIN, "Failed to release fingerprint device: {}", e);
+    }
+    Ok(results)
+}
+
+/// Deletes the `fingers` the user confirmed deleting, then reports each outcome in a summary
+/// dialog, the Activity sidebar and the history, and refreshes the enrolled list.
+async fn delete_fingers(state: &Rc<AppState>, window: &ApplicationWindow, fingers: &[FingerName]) {
+    // The Delete page is greyed out while busy, but a row's context menu isn't
+    let Some(operation) = state.begin_operation(Operation::Delete) else {
+        show_toast(
+            window,
+            adw::Toast::new("Wait for the running operation to finish"),
+        );
+        return;
+    };
+    match batch_delete_fingers(state, window, fingers, operation.cancel_token()).await {
+        Ok(results) => {
+            operation.set_succeeded(results.iter().all(|(_, r)| r.is_ok()));
+            for (finger, result) in &results {
+                match result {
+                    Ok(()) => {
+                        state.activity.log(
+                            ActivityKind::Success,
+                            &format!("{} deleted", finger.display_name()),
+                        );
+                        history::record(*finger, history::HistoryEvent::Deleted);
+                    }
+                    Err(e) => state.activity.log(
+                        ActivityKind::Failure,
+                        &format!("{} could not be deleted: {e}", finger.display_name()),
+                    ),
+                }
+            }
+            show_batch_delete_summary(window, &results);
+            if results
+                .iter()
+                .any(|(_, result)| result.as_ref().is_err_and(dbus::is_connection_lost))
+            {
+                let _ = window.activate_action("win.connection-lost", None);
+            }
+        }
+        Err(e) => {
+            show_error_dialog(window, &format!("Deletion failed: {e:#}"));
+            report_connection_lost(window, &e);
+        }
+    }
+    drop(operation);
+    let _ = window.activate_action("win.refresh", None);
+}
+
+fn show_batch_delete_summary(
+    window: &ApplicationWindow,
+    results: &[(FingerName, zbus::Result<()>)],
+) {
+    let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+    let details = results
+        .iter()
+        .map(|(finger, result)| match result {
+            Ok(()) => format!("{}: deleted", finger.display_name()),
+            // Someone else deleted it since the list was loaded
+            Err(e) if fprintd_error_name(e) == Some("NoEnrolledPrints") => {
+                format!("{}: not enrolled", finger.display_name())
+            }
+            Err(e) => format!("{}: {e}", finger.display_name()),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let heading = if failed == 0 {
+        "Fingerprints Deleted".to_string()
+    } else {
+        format!("{failed} of {} Deletions Failed", results.len())
+    };
+    dialogs::message_dialog(window, &heading, Some(&details)).present();
+}
+
+fn fill_delete_list(list: &ListBox, selection: &FingerChecks) {
+    let list_weak = list.downgrade();
+    let selection = selection.clone();
+    glib::spawn_future_local(async move {
+        let result = run_off_main_thread(async { Ok(get_enrolled_fingers().await?) }).await;
+        let Some(list) = list_weak.upgrade() else {
+            return;
+        };
+
+        while let Some(child) = list.first_child() {
+            list.remove(&child);
+        }
+        selection.borrow_mut().clear();
+
+        let placeholder = match &result {
+            Ok(_) => "No fingerprints enrolled".to_string(),
+            Err(e) => format!("Couldn't load the enrolled fingerprints: {e:#}"),
+        };
+        let placeholder = Label::builder()
+            .label(placeholder)
+            .wrap(true)
+            .margin_top(12)
+            .margin_bottom(12)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+        placeholder.add_css_class("dim-label");
+        list.set_placeholder(Some(&placeholder));
+
+        let mut fingers: Vec<FingerName> = result
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|name| FingerName::try_from(name.as_str()).ok())
+            .collect();
+        fingers.sort();
+        for finger in fingers {
+            let check = gtk4::CheckButton::new();
+            let row = adw::ActionRow::builder()
+                .title(finger.display_name())
+                .build();
+            row.add_prefix(&check);
+            row.set_activatable_widget(Some(&check));
+            list.append(&row);
+            selection.borrow_mut().push((finger, check));
+        }
+    });
+}
+
+/// Shows `toast` on the window's toast overlay.
+fn show_toast(window: &ApplicationWindow, toast: adw::Toast) {
+    if let Some(overlay) = window.content().and_downcast::<adw::ToastOverlay>() {
+        overlay.add_toast(toast);
+    }
+}
 
-    stack.add_named(&enroll_page, Some("enroll"));
-    stack.add_named(&verify_page, Some("verify"));
-    stack.add_named(&delete_page, Some("delete"));
+fn show_error_dialog(window: &ApplicationWindow, message: &str) {
+    dialogs::message_dialog(window, "Error", Some(message)).present();
+}
+
+/// A collapsed "Show Details" expander for raw error output, and the label inside it.
+fn create_error_details() -> (gtk4::Expander, Label) {
+    let label = Label::new(None);
+    label.set_selectable(true);
+    label.set_wrap(true);
+    label.set_xalign(0.0);
+    label.add_css_class("monospace");
+    let expander = gtk4::Expander::new(Some("Show Details"));
+    expander.set_child(Some(&label));
+    (expander, label)
+}
 
-    stack.set_visible_child_name("main");
+/// Logs `error` under its GLib error domain and code, then shows it to the user.
+fn show_app_error(window: &ApplicationWindow, error: &AppError) {
+    let error_is_connection_lost = error.is_connection_lost();
+    let code = error.code() as i32;
+    let error = glib::Error::from(error);
+    glib::g_warning!(
+        LOG_DOMAIN,
+        "{} {code}: {}",
+        error.domain().as_str(),
+        error.message()
+    );
+    show_error_dialog(window, error.message());
+    if error_is_connection_lost {
+        let _ = window.activate_action("win.connection-lost", None);
+    }
+}
 
-    window.set_child(Some(&stack));
+/// Hands `error` to the `win.connection-lost` supervisor if the bus connection was lost on
+/// the way.
+fn report_connection_lost(window: &ApplicationWindow, error: &anyhow::Error) {
+    let lost = error.chain().any(|cause| {
+        cause
+            .downcast_ref::<zbus::Error>()
+            .is_some_and(dbus::is_connection_lost)
+            || cause
+                .downcast_ref::<AppError>()
+                .is_some_and(AppError::is_connection_lost)
+    });
+    if lost {
+        let _ = window.activate_action("win.connection-lost", None);
+    }
+}
+
+fn create_enrolled_finger_row(
+    finger: FingerName,
+    state: &Rc<AppState>,
+    window: &ApplicationWindow,
+) -> FingerStatusRow {
+    let row = FingerStatusRow::new(finger);
+
+    let actions = gio::SimpleActionGroup::new();
+
+    let verify_action = gio::SimpleAction::new("verify", None);
+    let window_weak = window.downgrade();
+    verify_action.connect_activate(move |_, _| {
+        if let Some(window) = window_weak.upgrade() {
+            let _ = window.activate_action("win.show-verify", Some(&finger.as_str().to_variant()));
+        }
+    });
+    actions.add_action(&verify_action);
+
+    let reenroll_action = gio::SimpleAction::new("reenroll", None);
+    let window_weak = window.downgrade();
+    reenroll_action.connect_activate(move |_, _| {
+        if let Some(window) = window_weak.upgrade() {
+            let _ =
+                window.activate_action("win.enroll-finger", Some(&finger.as_str().to_variant()));
+        }
+    });
+    actions.add_action(&reenroll_action);
+
+    let delete_action = gio::SimpleAction::new("delete", None);
+    let window_weak = window.downgrade();
+    let state_weak = Rc::downgrade(state);
+    delete_action.connect_activate(move |_, _| {
+        let (Some(window), Some(state)) = (window_weak.upgrade(), state_weak.upgrade()) else {
+            return;
+        };
+        let confirm_dialog = dialogs::confirm_delete_dialog(&[finger], &window);
+        confirm_dialog.connect_response(None, move |_, response| {
+            if response != "delete" {
+                return;
+            }
+            let window = window.clone();
+            let state = state.clone();
+            glib::spawn_future_local(async move {
+                delete_fingers(&state, &window, &[finger]).await;
+            });
+        });
+        confirm_dialog.present();
+    });
+
+    actions.add_action(&delete_action);
+
+    let copy_action = gio::SimpleAction::new("copy", None);
+    let row_weak = row.downgrade();
+    copy_action.connect_activate(move |_, _| {
+        if let Some(row) = row_weak.upgrade() {
+            row.clipboard().set_text(finger.as_str());
+        }
+    });
+    actions.add_action(&copy_action);
+
+    // Only an enrolled print can be verified or deleted
+    for action in [&verify_action, &delete_action] {
+        row.bind_property("enrolled", action, "enabled")
+            .sync_create()
+            .build();
+    }
+
+    row.insert_action_group("row", Some(&actions));
+
+    let menu = gio::Menu::new();
+    menu.append(Some("Verify this finger"), Some("row.verify"));
+    menu.append(Some("Re-enroll"), Some("row.reenroll"));
+    menu.append(Some("Delete"), Some("row.delete"));
+    menu.append(Some("Copy finger name"), Some("row.copy"));
+
+    let popover = gtk4::PopoverMenu::from_model(Some(&menu));
+    popover.set_parent(&row);
+    popover.set_has_arrow(false);
+
+    // Mouse users get the menu on right click, touch users on long press.
+    let right_click = gtk4::GestureClick::builder().button(3).build();
+    let popover_weak = popover.downgrade();
+    right_click.connect_pressed(move |gesture, _, x, y| {
+        gesture.set_state(gtk4::EventSequenceState::Claimed);
+        if let Some(popover) = popover_weak.upgrade() {
+            popover.set_pointing_to(Some(&gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+            popover.popup();
+        }
+    });
+    row.add_controller(right_click);
+
+    let long_press = gtk4::GestureLongPress::new();
+    let popover_weak = popover.downgrade();
+    long_press.connect_pressed(move |_, x, y| {
+        if let Some(popover) = popover_weak.upgrade() {
+            popover.set_pointing_to(Some(&gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+            popover.popup();
+        }
+    });
+    row.add_controller(long_press);
+
+    row.connect_destroy(move |_| {
+        popover.unparent();
+    });
+
+    row
+}
+
+fn populate_enrolled_list(
+    state: &Rc<AppState>,
+    window: &ApplicationWindow,
+    status: &Label,
+    list: &ListBox,
+) -> glib::JoinHandle<anyhow::Result<()>> {
+    let window_weak = window.downgrade();
+    let status_weak = status.downgrade();
+    let list_weak = list.downgrade();
+    let state = state.clone();
+    glib::spawn_future_local(async move {
+        let result = run_off_main_thread(async { Ok(get_enrolled_fingers().await?) }).await;
+        let (Some(window), Some(status), Some(list)) = (
+            window_weak.upgrade(),
+            status_weak.upgrade(),
+            list_weak.upgrade(),
+        ) else {
+            return Ok(());
+        };
+
+        match result {
+            Ok(fingers) => {
+                let enrolled: HashSet<FingerName> = fingers
+                    .iter()
+                    .filter_map(|name| FingerName::try_from(name.as_str()).ok())
+                    .collect();
+                if enrolled.is_empty() {
+                    status.set_text("No fingerprints enrolled");
+                } else {
+                    status.set_text("Enrolled fingerprints:");
+                }
+                // The rows come from the list's model and are updated in place
+                let mut child = list.first_child();
+                while let Some(widget) = child {
+                    if let Some(row) = widget.downcast_ref::<FingerStatusRow>() {
+                        let enrolled = row
+                            .finger_name()
+                            .is_some_and(|finger| enrolled.contains(&finger));
+                        row.set_enrolled(enrolled);
+                    }
+                    child = widget.next_sibling();
+                }
+                list.set_visible(true);
+                state.set_enrolled_fingers(enrolled);
+                Ok(())
+            }
+            Err(e) => {
+                status.set_text(&format!("Error loading fingerprints: {e:#}"));
+                list.set_visible(false);
+                report_connection_lost(&window, &e);
+                Err(e)
+            }
+        }
+    })
+}
+
+/// Sends the number of enrolled fingers every `interval` until the receiver is dropped.
+///
+/// fprintd has no signal for prints being added or removed, so other tools' changes can only
+/// be noticed by polling. Runs on the tokio runtime, so a lost connection is reported through
+/// `connection_lost` for the UI thread to handle; the next poll uses its replacement.
+async fn poll_enrolled_count(
+    interval: std::time::Duration,
+    tx: tokio::sync::watch::Sender<usize>,
+    connection_lost: async_channel::Sender<()>,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    while !tx.is_closed() {
+        ticker.tick().await;
+        let enrolled = async {
+            let conn = dbus::connect().await?;
+            let proxy = dbus::device_proxy(&conn).await?;
+            proxy.list_enrolled_fingers(&dbus::target_user()).await
+        }
+        .await;
+        let count = match enrolled {
+            Ok(fingers) => fingers.len(),
+            // fprintd reports an empty list as an error
+            Err(e) if fprintd_error_name(&e) == Some("NoEnrolledPrints") => 0,
+            Err(e) if dbus::is_connection_lost(&e) => {
+                let _ = connection_lost.try_send(());
+                continue;
+            }
+            Err(e) => {
+                glib::g_debug!(LOG_DOMAIN, "Failed to count enrolled fingers: {}", e);
+                continue;
+            }
+        };
+        tx.send_if_modified(|current| std::mem::replace(current, count) != count);
+    }
+}
+
+/// A header bar for one navigation page, with the controls every page shares: the operation
+/// spinner, the Activity sidebar toggle and the primary menu.
+fn create_page_header(
+    state: &Rc<AppState>,
+    activity_split_view: &adw::OverlaySplitView,
+) -> adw::HeaderBar {
+    let header_bar = adw::HeaderBar::new();
+
+    let activity_button = gtk4::ToggleButton::builder()
+        .icon_name("document-open-recent-symbolic")
+        .tooltip_text("Activity")
+        .build();
+    activity_button
+        .bind_property("active", activity_split_view, "show-sidebar")
+        .bidirectional()
+        .sync_create()
+        .build();
+    let primary_menu = gio::Menu::new();
+    primary_menu.append(Some("Preferences"), Some("app.preferences"));
+    primary_menu.append(Some("About Fingerprint Manager"), Some("app.about"));
+    primary_menu.append(Some("Quit"), Some("app.quit"));
+    let menu_button = gtk4::MenuButton::builder()
+        .icon_name("open-menu-symbolic")
+        .tooltip_text("Main Menu")
+        .menu_model(&primary_menu)
+        .build();
+    header_bar.pack_end(&menu_button);
+    header_bar.pack_end(&activity_button);
+
+    // Spins while any device operation runs
+    let operation_spinner = gtk4::Spinner::new();
+    header_bar.pack_start(&operation_spinner);
+    let spinner_weak = operation_spinner.downgrade();
+    connect_app_events(state, move |event| {
+        let Some(spinner) = spinner_weak.upgrade() else {
+            return ControlFlow::Break;
+        };
+        match event {
+            AppEvent::OperationStarted(_) => spinner.start(),
+            AppEvent::OperationCompleted(..) => spinner.stop(),
+            _ => {}
+        }
+        ControlFlow::Continue
+    });
+
+    header_bar
+}
+
+fn enrolled_count_subtitle(count: usize) -> String {
+    match count {
+        0 => "No fingers enrolled".to_string(),
+        1 => "1 finger enrolled".to_string(),
+        n => format!("{n} fingers enrolled"),
+    }
+}
+
+fn build_ui(app: &Application) {
+    let window = ApplicationWindow::builder()
+        .application(app)
+        .title("Fingerprint Manager")
+        .default_width(400)
+        .default_height(300)
+        .build();
+
+    let state = AppState::new(whoami::username());
+    let nav = adw::NavigationView::new();
+
+    // Create main menu
+    let main_page = create_page_content("Main Menu", &state, &window, &nav);
+
+    // Add enrolled fingers list
+    let enrolled_status = Label::new(Some("Loading enrolled fingerprints..."));
+    enrolled_status.set_hexpand(true);
+    let refresh_button = Button::from_icon_name("view-refresh-symbolic");
+    refresh_button.set_tooltip_text(Some("Refresh"));
+    refresh_button.set_action_name(Some("win.reload"));
+    let enrolled_header = GBox::new(Orientation::Horizontal, 10);
+    enrolled_header.append(&enrolled_status);
+    enrolled_header.append(&refresh_button);
+    let enrolled_list = ListBox::new();
+    enrolled_list.set_selection_mode(SelectionMode::None);
+    enrolled_list.add_css_class("boxed-list");
+    enrolled_list.set_visible(false);
+
+    disable_while_busy(&state, &enrolled_list);
+
+    // One row per finger in anatomical order, behind a filter for the search bar
+    let mut fingers = FingerName::ALL;
+    fingers.sort();
+    let fingers_model = gio::ListStore::new::<FingerObject>();
+    for finger in fingers {
+        fingers_model.append(&FingerObject::new(finger, get_finger_icon(finger.as_str())));
+    }
+    let search_filter = gtk4::StringFilter::new(Some(gtk4::PropertyExpression::new(
+        FingerObject::static_type(),
+        None::<&gtk4::Expression>,
+        "display-name",
+    )));
+    search_filter.set_match_mode(gtk4::StringFilterMatchMode::Substring);
+    let filtered_fingers =
+        gtk4::FilterListModel::new(Some(fingers_model), Some(search_filter.clone()));
+    let row_state = state.clone();
+    let window_weak = window.downgrade();
+    enrolled_list.bind_model(Some(&filtered_fingers), move |item| {
+        let finger = item
+            .downcast_ref::<FingerObject>()
+            .and_then(FingerObject::finger)
+            .expect("fingers model holds valid FingerObjects");
+        // Rows are recreated when the filter lets a finger back in, so start from the last
+        // known state rather than waiting for the next refresh
+        let row = match window_weak.upgrade() {
+            Some(window) => create_enrolled_finger_row(finger, &row_state, &window),
+            None => FingerStatusRow::new(finger),
+        };
+        row.set_enrolled(row_state.enrolled_fingers.borrow().contains(&finger));
+        row.upcast()
+    });
+
+    let search_entry = gtk4::SearchEntry::builder()
+        .placeholder_text("Search fingers")
+        .build();
+    search_entry.connect_search_changed(move |entry| {
+        search_filter.set_search(Some(&entry.text()));
+    });
+    let search_bar = gtk4::SearchBar::builder()
+        .child(&search_entry)
+        .key_capture_widget(&window)
+        .build();
+    search_bar.connect_entry(&search_entry);
+    // Toggled by the header bar button and Ctrl+F
+    window.add_action(&gio::PropertyAction::new(
+        "search",
+        &search_bar,
+        "search-mode-enabled",
+    ));
+    app.set_accels_for_action("win.search", &["<Control>f"]);
+
+    main_page.append(&enrolled_header);
+    main_page.append(&search_bar);
+    main_page.append(&enrolled_list);
+
+    // The other sections are pushed on top of the list
+    let section_buttons = GBox::new(Orientation::Horizontal, 10);
+    section_buttons.set_halign(gtk4::Align::Center);
+    section_buttons.set_homogeneous(true);
+    for (label, tag) in [
+        ("Enroll", "enroll"),
+        ("Verify", "verify"),
+        ("Delete", "delete"),
+    ] {
+        let button = Button::with_label(label);
+        button.set_action_name(Some("win.show-page"));
+        button.set_action_target_value(Some(&tag.to_variant()));
+        section_buttons.append(&button);
+    }
+    main_page.append(&section_buttons);
+
+    let toast_overlay = adw::ToastOverlay::new();
+
+    let version_banner = adw::Banner::new(
+        "Your fprintd version may not be fully supported. Some features may not work. \
+         Update to fprintd 1.92 or later for best results.",
+    );
+    version_banner.set_button_label(Some("Dismiss"));
+    version_banner.connect_button_clicked(|banner| {
+        banner.set_revealed(false);
+    });
+
+    let banner_weak = version_banner.downgrade();
+    glib::spawn_future_local(async move {
+        let outdated = run_off_main_thread(async {
+            let conn = dbus::connect()
+                .await
+                .context("connecting to the system bus")?;
+            dbus::fprintd_is_outdated(&conn)
+                .await
+                .context("introspecting the fprintd device")
+        })
+        .await;
+        if let Ok(true) = outdated {
+            if let Some(banner) = banner_weak.upgrade() {
+                banner.set_revealed(true);
+            }
+        }
+    });
+
+    let permission_banner = adw::Banner::new(
+        "You may not have permission to manage fingerprints. Contact your system administrator.",
+    );
+    permission_banner.set_button_label(Some("Open polkit Rules"));
+    permission_banner.connect_button_clicked(move |_| {
+        // Goes through polkit with an admin prompt, to show whether elevated access works. The
+        // user name is its own argument, so it is never parsed by a shell.
+        let user = dbus::target_user();
+        let argv = [
+            OsStr::new("pkexec"),
+            OsStr::new("fprintd-list"),
+            OsStr::new(&user),
+        ];
+        if let Err(e) = gio::Subprocess::newv(&argv, gio::SubprocessFlags::NONE) {
+            glib::g_warning!(LOG_DOMAIN, "Failed to run fprintd-list: {}", e);
+        }
+    });
+
+    let permission_state = state.clone();
+    let banner_weak = permission_banner.downgrade();
+    glib::spawn_future_local(async move {
+        let level = run_off_main_thread(async {
+            let conn = dbus::connect()
+                .await
+                .context("connecting to the system bus")?;
+            Ok(dbus::check_fprintd_policykit_rules(&conn).await)
+        })
+        .await
+        .unwrap_or_default();
+        permission_state.permission_level.set(level);
+        if let Some(banner) = banner_weak.upgrade() {
+            banner.set_revealed(level == PermissionLevel::Restricted);
+        }
+    });
+
+    // Offer to retry an enrollment that was cut short in an earlier session
+    let interrupted_banner = adw::Banner::new("");
+    interrupted_banner.set_button_label(Some("Retry"));
+    if let Some((finger, stage)) = settings::interrupted_enrollment() {
+        let window_weak = window.downgrade();
+        interrupted_banner.connect_button_clicked(move |banner| {
+            banner.set_revealed(false);
+            if let Some(window) = window_weak.upgrade() {
+                let _ = window
+                    .activate_action("win.enroll-finger", Some(&finger.as_str().to_variant()));
+            }
+        });
+
+        let banner_weak = interrupted_banner.downgrade();
+        glib::spawn_future_local(async move {
+            let Ok(enrolled) =
+                run_off_main_thread(async { Ok(get_enrolled_fingers().await?) }).await
+            else {
+                return;
+            };
+            if enrolled.iter().any(|name| name == finger.as_str()) {
+                // Enrolled since, e.g. with fprintd-enroll
+                settings::clear_enrollment_stage();
+                return;
+            }
+            if let Some(banner) = banner_weak.upgrade() {
+                banner.set_title(&format!(
+                    "Previous enrollment for {} was interrupted at stage {}. \
+                     Would you like to retry?",
+                    finger.display_name(),
+                    stage
+                ));
+                banner.set_revealed(true);
+            }
+        });
+    }
+
+    // Set up enrolled fingers list update
+    populate_enrolled_list(&state, &window, &enrolled_status, &enrolled_list);
+
+    // Re-read the list on demand, since other tools may enroll or delete fingers meanwhile.
+    // win.refresh is for the app's own reloads, e.g. after a deletion; win.reload is the
+    // user's, and reports how it went
+    let announce_refresh = Rc::new(std::cell::Cell::new(false));
+    let schedule_refresh = {
+        let refresh_state = state.clone();
+        let window_weak = window.downgrade();
+        let status_weak = enrolled_status.downgrade();
+        let list_weak = enrolled_list.downgrade();
+        let overlay_weak = toast_overlay.downgrade();
+        let announce_refresh = announce_refresh.clone();
+        Rc::new(move |user_requested: bool| {
+            if user_requested {
+                announce_refresh.set(true);
+            }
+            // Coalesce bursts of refresh requests (e.g. after a batch delete) into one reload
+            // on the next idle cycle
+            if refresh_state.refresh_pending.replace(true) {
+                return;
+            }
+            let refresh_state = refresh_state.clone();
+            let window_weak = window_weak.clone();
+            let status_weak = status_weak.clone();
+            let list_weak = list_weak.clone();
+            let overlay_weak = overlay_weak.clone();
+            let announce_refresh = announce_refresh.clone();
+            glib::idle_add_local_once(move || {
+                refresh_state.refresh_pending.set(false);
+                let announce = announce_refresh.replace(false);
+                let (Some(window), Some(status), Some(list)) = (
+                    window_weak.upgrade(),
+                    status_weak.upgrade(),
+                    list_weak.upgrade(),
+                ) else {
+                    return;
+                };
+                let refresh = populate_enrolled_list(&refresh_state, &window, &status, &list);
+                glib::spawn_future_local(async move {
+                    let result = refresh.await;
+                    let Some(overlay) = overlay_weak.upgrade().filter(|_| announce) else {
+                        return;
+                    };
+                    let title = match result {
+                        Ok(Ok(())) => "Refreshed".to_string(),
+                        Ok(Err(e)) => format!("Refresh failed: {e:#}"),
+                        Err(_) => "Refresh failed".to_string(),
+                    };
+                    overlay.add_toast(adw::Toast::new(&title));
+                });
+            });
+        })
+    };
+    let refresh_action = gio::SimpleAction::new("refresh", None);
+    let schedule = schedule_refresh.clone();
+    refresh_action.connect_activate(move |_, _| schedule(false));
+    window.add_action(&refresh_action);
+    let reload_action = gio::SimpleAction::new("reload", None);
+    reload_action.connect_activate(move |_, _| schedule_refresh(true));
+    window.add_action(&reload_action);
+    app.set_accels_for_action("win.reload", &["<Control>r"]);
+
+    let about_action = gio::SimpleAction::new("about", None);
+    let window_weak = window.downgrade();
+    about_action.connect_activate(move |_, _| {
+        let window_weak = window_weak.clone();
+        glib::spawn_future_local(async move {
+            let version = match dbus::connect().await {
+                Ok(conn) => dbus::get_fprintd_version(&conn).await,
+                Err(_) => None,
+            };
+            let Some(window) = window_weak.upgrade() else {
+                return;
+            };
+            let backend = match version {
+                Some(version) => format!("Backend: fprintd {version}"),
+                None => "Backend: fprintd (version unknown)".to_string(),
+            };
+            let about = adw::AboutWindow::builder()
+                .transient_for(&window)
+                .modal(true)
+                .application_name("Fingerprint Manager")
+                .version(env!("CARGO_PKG_VERSION"))
+                .website("https://github.com/vadika/rs-fprintui")
+                .comments(backend)
+                .build();
+            about.present();
+        });
+    });
+    app.add_action(&about_action);
+
+    let preferences_action = gio::SimpleAction::new("preferences", None);
+    let preferences_state = state.clone();
+    let window_weak = window.downgrade();
+    preferences_action.connect_activate(move |_, _| {
+        if let Some(window) = window_weak.upgrade() {
+            ui::preferences::create_preferences_window(&window, &preferences_state).present();
+        }
+    });
+    app.add_action(&preferences_action);
+    app.set_accels_for_action("app.preferences", &["<Control>comma"]);
+
+    // Quitting mid-session cancels the running operation and closes the windows. The
+    // operation's application hold keeps the process alive until it has stopped the session
+    // and released the device
+    let quit_action = gio::SimpleAction::new("quit", None);
+    let quit_state = state.clone();
+    let window_weak = window.downgrade();
+    let app_weak = app.downgrade();
+    quit_action.connect_activate(move |_, _| {
+        let (Some(window), Some(app)) = (window_weak.upgrade(), app_weak.upgrade()) else {
+            return;
+        };
+        let heading = match quit_state.active_operation.get() {
+            None => {
+                app.quit();
+                return;
+            }
+            Some(Operation::Enroll) => "Enrollment is in progress. Quit anyway?",
+            Some(Operation::Verify) => "Verification is in progress. Quit anyway?",
+            Some(Operation::Delete) => "Deletion is in progress. Quit anyway?",
+        };
+        let quit_state = quit_state.clone();
+        glib::spawn_future_local(async move {
+            let dialog = dialogs::confirm_destructive_dialog(
+                &window,
+                heading,
+                "The device session will be stopped and its progress lost.",
+                "_Quit",
+            );
+            if dialog.choose_future().await == "confirm" {
+                quit_state.cancel_operation();
+                for window in app.windows() {
+                    window.close();
+                }
+            }
+        });
+    });
+    app.add_action(&quit_action);
+
+    // The hold lets a running operation outlive the window, but nobody could see or stop it
+    let close_state = Rc::downgrade(&state);
+    window.connect_close_request(move |_| {
+        if let Some(state) = close_state.upgrade() {
+            state.cancel_operation();
+        }
+        glib::Propagation::Proceed
+    });
+    app.set_accels_for_action("app.quit", &["<Control>q"]);
+
+    // Titles the fingerprint list's header bar
+    let window_title = adw::WindowTitle::new("Fingerprint Manager", "");
+
+    // Show the enrolled count under the title, including changes made elsewhere
+    let (count_tx, mut count_rx) = tokio::sync::watch::channel(0);
+    let (lost_tx, lost_rx) = async_channel::bounded(1);
+    tokio::spawn(poll_enrolled_count(
+        settings::enrolled_poll_interval(),
+        count_tx,
+        lost_tx,
+    ));
+    let window_weak = window.downgrade();
+    glib::spawn_future_local(async move {
+        while lost_rx.recv().await.is_ok() {
+            let Some(window) = window_weak.upgrade() else {
+                break;
+            };
+            let _ = window.activate_action("win.connection-lost", None);
+        }
+    });
+    let title_weak = window_title.downgrade();
+    glib::spawn_future_local(async move {
+        while count_rx.changed().await.is_ok() {
+            let count = *count_rx.borrow_and_update();
+            let Some(title) = title_weak.upgrade() else {
+                break;
+            };
+            title.set_subtitle(&enrolled_count_subtitle(count));
+        }
+    });
+    // Our own refreshes and deletions update it right away
+    let title_weak = window_title.downgrade();
+    let count_state = Rc::downgrade(&state);
+    connect_app_events(&state, move |event| {
+        let (Some(title), Some(state)) = (title_weak.upgrade(), count_state.upgrade()) else {
+            return ControlFlow::Break;
+        };
+        if *event == AppEvent::EnrolledFingersChanged {
+            title.set_subtitle(&enrolled_count_subtitle(
+                state.enrolled_fingers.borrow().len(),
+            ));
+        }
+        ControlFlow::Continue
+    });
+
+    // Create other pages
+    let enroll_page = create_page_content("Enroll Fingerprint", &state, &window, &nav);
+    let verify_page = create_page_content("Verify Fingerprint", &state, &window, &nav);
+    let delete_page = create_page_content("Delete Fingerprint", &state, &window, &nav);
+
+    // Session activity log, shown as a sidebar from the header bars
+    let activity_view = gtk4::TextView::builder()
+        .buffer(state.activity.buffer())
+        .editable(false)
+        .cursor_visible(false)
+        .wrap_mode(gtk4::WrapMode::WordChar)
+        .left_margin(6)
+        .right_margin(6)
+        .build();
+    let activity_scroller = gtk4::ScrolledWindow::builder()
+        .child(&activity_view)
+        .width_request(260)
+        .vexpand(true)
+        .build();
+    let activity_sidebar = adw::ToolbarView::builder()
+        .content(&activity_scroller)
+        .build();
+    activity_sidebar.add_top_bar(
+        &adw::HeaderBar::builder()
+            .title_widget(&adw::WindowTitle::new("Activity", ""))
+            .build(),
+    );
+    let activity_split_view = adw::OverlaySplitView::builder()
+        .sidebar(&activity_sidebar)
+        .content(&nav)
+        .sidebar_position(gtk4::PackType::End)
+        .show_sidebar(false)
+        .build();
+
+    let search_button = gtk4::ToggleButton::builder()
+        .icon_name("system-search-symbolic")
+        .tooltip_text("Search Fingers")
+        .action_name("win.search")
+        .build();
+    // Notices and the device and user pickers are about the list, so they sit on its page
+    let main_bars: [gtk4::Widget; 5] = [
+        version_banner.upcast(),
+        permission_banner.upcast(),
+        interrupted_banner.upcast(),
+        create_device_selector(&state, &window).upcast(),
+        create_user_selector(&state, &window).upcast(),
+    ];
+
+    // The first page added is the root; the others are pushed by tag. Each page has its own
+    // header bar, which shows the page title and, once pushed, a Back button.
+    for (child, title, tag) in [
+        (&main_page, "Fingerprint Manager", "main"),
+        (&enroll_page, "Enroll Fingerprint", "enroll"),
+        (&verify_page, "Verify Fingerprint", "verify"),
+        (&delete_page, "Delete Fingerprint", "delete"),
+    ] {
+        let header_bar = create_page_header(&state, &activity_split_view);
+        let toolbar_view = adw::ToolbarView::builder().content(child).build();
+        toolbar_view.add_top_bar(&header_bar);
+        if tag == "main" {
+            header_bar.set_title_widget(Some(&window_title));
+            header_bar.pack_start(&search_button);
+            for bar in &main_bars {
+                toolbar_view.add_top_bar(bar);
+            }
+        }
+        let page = adw::NavigationPage::with_tag(&toolbar_view, title, tag);
+        // Start keyboard users on the page's first control (the finger selector) rather than
+        // leaving focus on the header bar
+        page.connect_shown(|page| {
+            page.child_focus(gtk4::DirectionType::TabForward);
+        });
+        nav.add(&page);
+    }
+
+    // Switches to a page by tag; Escape leads back to the fingerprint list
+    let show_page_action = gio::SimpleAction::new("show-page", Some(glib::VariantTy::STRING));
+    let nav_weak = nav.downgrade();
+    show_page_action.connect_activate(move |_, parameter| {
+        let (Some(nav), Some(tag)) = (
+            nav_weak.upgrade(),
+            parameter.and_then(|p| p.get::<String>()),
+        ) else {
+            return;
+        };
+        show_page(&nav, &tag);
+    });
+    window.add_action(&show_page_action);
+
+    // Bubble phase, so a focused entry still gets Ctrl+V to paste and the search bar still
+    // gets Escape to close; application accels would take those keys first
+    let page_shortcuts = gtk4::ShortcutController::new();
+    page_shortcuts.set_propagation_phase(gtk4::PropagationPhase::Bubble);
+    for (key, modifiers, page) in [
+        (gdk::Key::e, gdk::ModifierType::CONTROL_MASK, "enroll"),
+        (gdk::Key::v, gdk::ModifierType::CONTROL_MASK, "verify"),
+        (gdk::Key::d, gdk::ModifierType::CONTROL_MASK, "delete"),
+        (gdk::Key::Escape, gdk::ModifierType::empty(), "main"),
+    ] {
+        let shortcut = gtk4::Shortcut::builder()
+            .trigger(&gtk4::KeyvalTrigger::new(key, modifiers))
+            .action(&gtk4::NamedAction::new("win.show-page"))
+            .arguments(&page.to_variant())
+            .build();
+        page_shortcuts.add_shortcut(shortcut);
+    }
+    window.add_controller(page_shortcuts);
+
+    // Full-page success/failure feedback, triggered through the win.feedback action
+    let feedback_icon = Image::new();
+    feedback_icon.set_pixel_size(128);
+    feedback_icon.add_css_class("feedback-icon");
+    let feedback_revealer = gtk4::Revealer::builder()
+        .transition_type(gtk4::RevealerTransitionType::SlideUp)
+        .halign(gtk4::Align::Center)
+        .valign(gtk4::Align::Center)
+        .can_target(false)
+        .child(&feedback_icon)
+        .build();
+    // Shown instead of the regular content while fprintd can't be reached
+    let retry_button = Button::with_label("Retry");
+    retry_button.set_halign(gtk4::Align::Center);
+    retry_button.add_css_class("pill");
+    retry_button.add_css_class("suggested-action");
+    retry_button.set_action_name(Some("win.check-service"));
+    let service_status = adw::StatusPage::builder()
+        .icon_name("dialog-warning-symbolic")
+        .title("fprintd is not running")
+        .description("The fingerprint service could not be reached. Make sure fprintd is installed and try again.")
+        .child(&retry_button)
+        .build();
+    let service_status_view = adw::ToolbarView::builder().content(&service_status).build();
+    service_status_view.add_top_bar(&adw::HeaderBar::new());
+
+    let root_stack = gtk4::Stack::new();
+    root_stack.add_named(&activity_split_view, Some("content"));
+    root_stack.add_named(&service_status_view, Some("unavailable"));
+
+    let check_service_action = gio::SimpleAction::new("check-service", None);
+    let root_weak = root_stack.downgrade();
+    let window_weak = window.downgrade();
+    let service_state = Rc::downgrade(&state);
+    check_service_action.connect_activate(move |_, _| {
+        let root_weak = root_weak.clone();
+        let window_weak = window_weak.clone();
+        let service_state = service_state.clone();
+        glib::spawn_future_local(async move {
+            let available = run_off_main_thread(async { Ok(dbus::fprintd_available().await) })
+                .await
+                .unwrap_or(false);
+            let (Some(root), Some(window)) = (root_weak.upgrade(), window_weak.upgrade()) else {
+                return;
+            };
+            let was_available = root.visible_child_name().as_deref() == Some("content");
+            root.set_visible_child_name(if available { "content" } else { "unavailable" });
+            if available != was_available {
+                if let Some(state) = service_state.upgrade() {
+                    state.emit(AppEvent::DeviceStateChanged(if available {
+                        DeviceReadiness::Available
+                    } else {
+                        DeviceReadiness::Unavailable
+                    }));
+                }
+            }
+            if available && !was_available {
+                // fprintd may have restarted and numbered its devices differently
+                dbus::forget_device_path();
+                let _ = window.activate_action("win.refresh", None);
+            }
+        });
+    });
+    window.add_action(&check_service_action);
+
+    // Every lost bus connection is reported here. The running operation can't finish on it,
+    // so it is cancelled; then the connection is replaced, or the status page shown if
+    // fprintd stays out of reach.
+    let connection_lost_action = gio::SimpleAction::new("connection-lost", None);
+    let window_weak = window.downgrade();
+    let supervisor_state = Rc::downgrade(&state);
+    let reconnecting = Rc::new(std::cell::Cell::new(false));
+    connection_lost_action.connect_activate(move |_, _| {
+        if reconnecting.replace(true) {
+            return;
+        }
+        if let Some(state) = supervisor_state.upgrade() {
+            state.cancel_operation();
+        }
+        let window_weak = window_weak.clone();
+        let reconnecting = reconnecting.clone();
+        glib::spawn_future_local(async move {
+            let reconnected = run_off_main_thread(async { Ok(dbus::reconnect().await) })
+                .await
+                .unwrap_or(false);
+            reconnecting.set(false);
+            let Some(window) = window_weak.upgrade() else {
+                return;
+            };
+            if reconnected {
+                // fprintd restarts along with the bus and may number its devices differently
+                dbus::forget_device_path();
+                let _ = window.activate_action("win.refresh", None);
+            }
+            let _ = window.activate_action("win.check-service", None);
+        });
+    });
+    window.add_action(&connection_lost_action);
+
+    let feedback_overlay = gtk4::Overlay::new();
+    feedback_overlay.set_child(Some(&root_stack));
+    feedback_overlay.add_overlay(&feedback_revealer);
+
+    let feedback_action = gio::SimpleAction::new("feedback", Some(glib::VariantTy::BOOLEAN));
+    let revealer_weak = feedback_revealer.downgrade();
+    feedback_action.connect_activate(move |_, parameter| {
+        let Some(revealer) = revealer_weak.upgrade() else {
+            return;
+        };
+        let success = parameter.and_then(|p| p.get::<bool>()).unwrap_or(false);
+        if success {
+            feedback_icon.set_icon_name(Some("emblem-ok-symbolic"));
+            feedback_icon.remove_css_class("error");
+            feedback_icon.add_css_class("success");
+        } else {
+            feedback_icon.set_icon_name(Some("dialog-error-symbolic"));
+            feedback_icon.remove_css_class("success");
+            feedback_icon.add_css_class("error");
+        }
+        revealer.set_reveal_child(true);
+
+        let revealer_weak = revealer.downgrade();
+        glib::timeout_add_local_once(std::time::Duration::from_millis(1500), move || {
+            if let Some(revealer) = revealer_weak.upgrade() {
+                revealer.set_reveal_child(false);
+            }
+        });
+    });
+    window.add_action(&feedback_action);
+
+    toast_overlay.set_child(Some(&feedback_overlay));
+    window.set_content(Some(&toast_overlay));
     window.present();
+
+    check_service_action.activate(None);
+    ui::welcome::present_if_first_run(&window);
+    ui::whats_new::present_if_updated(&window);
 }
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    adw::init()?;
+const USAGE: &str = "Usage: fprintui [--help | --version]\n";
 
-    let app = Application::builder().application_id(APP_ID).build();
+/// What `--help` and `--version` print, or `None` for any other arguments.
+fn info_flag_text(arguments: &[String]) -> Option<String> {
+    match arguments {
+        [flag] if flag == "-h" || flag == "--help" => Some(USAGE.to_string()),
+        [flag] if flag == "--version" => Some(format!("fprintui {}\n", env!("CARGO_PKG_VERSION"))),
+        _ => None,
+    }
+}
 
-    // let conn = Connection::system().await?;
-    // let proxy = FprintDeviceProxy::new(&conn).await?;
+/// Handles the command line in the primary instance, so launching the app again presents the
+/// running window instead of starting a second one.
+fn handle_command_line(
+    app: &Application,
+    command_line: &gio::ApplicationCommandLine,
+) -> glib::ExitCode {
+    let arguments: Vec<String> = command_line
+        .arguments()
+        .iter()
+        .skip(1)
+        .map(|argument| argument.to_string_lossy().into_owned())
+        .collect();
+    if let Some(text) = info_flag_text(&arguments) {
+        command_line.print_literal(&text);
+        return ExitStatus::Success.into();
+    }
+    match arguments.as_slice() {
+        [] => {
+            app.activate();
+            ExitStatus::Success.into()
+        }
+        _ => {
+            command_line.printerr_literal(&format!(
+                "Unknown arguments: {}\n{USAGE}",
+                arguments.join(" ")
+            ));
+            ExitStatus::Usage.into()
+        }
+    }
+}
 
-    // proxy.release().await?;
+// Multi-threaded on purpose: `app.run()` blocks this thread in the GLib main loop, so a
+// current-thread runtime would never poll the tasks `run_off_main_thread` spawns. Only
+// `Send` values cross between the two; widgets and `AppState` stay in
+// `glib::spawn_future_local` tasks on the main thread.
+#[tokio::main(flavor = "multi_thread")]
+async fn main() -> std::process::ExitCode {
+    // Lets tokio-console attach to development builds
+    #[cfg(all(debug_assertions, feature = "console"))]
+    console_subscriber::init();
+
+    // Answered before the display and the bus are set up, so they work without either
+    let arguments: Vec<String> = std::env::args_os()
+        .skip(1)
+        .map(|argument| argument.to_string_lossy().into_owned())
+        .collect();
+    if let Some(text) = info_flag_text(&arguments) {
+        print!("{text}");
+        return ExitStatus::Success.into();
+    }
 
-    // let v = proxy.clone();
+    if let Err(e) = init().await {
+        eprintln!("fprintui: {e:#}");
+        let status = match e.downcast::<AppError>() {
+            Ok(e) => e.exit_status(),
+            Err(e) => e
+                .downcast::<zbus::Error>()
+                .map_or(ExitStatus::Failure, |e| AppError::from(e).exit_status()),
+        };
+        return status.into();
+    }
+    let app = Application::builder()
+        .application_id(APP_ID)
+        .flags(gio::ApplicationFlags::HANDLES_COMMAND_LINE)
+        .build();
+
+    app.connect_startup(|_| load_css());
+    app.connect_command_line(handle_command_line);
 
     app.connect_activate(move |app| {
-        // let value = v.clone();
+        // Launching again while running lands here too; reuse the window and its state
+        if let Some(window) = app.active_window() {
+            window.present();
+            return;
+        }
         build_ui(app);
-        // glib::spawn_future_local(async move {
-        //     // let _ = value.claim(&whoami::username()).await;
-        // });
-    });
-    // app.connect_shutdown(move |_| {
-    //     let value = proxy.clone();
-    //     glib::spawn_future_local(async move {
-    //         // let _ = value.release().await;
-    //     });
-    // });
-    app.run();
+    });
+    // The command line handler picks the status; the GUI itself always exits successfully
+    let status = app.run().value();
+    std::process::ExitCode::from(u8::try_from(status).unwrap_or(ExitStatus::Failure as u8))
+}
 
+/// Everything that has to work before the application can start.
+async fn init() -> anyhow::Result<()> {
+    dbus::check_names();
+    adw::init()?;
+    gio::resources_register_include!("fprintui.gresource")?;
+    dbus::connect()
+        .await
+        .context("Couldn't connect to the system bus")?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn admin_when_any_group_is_an_admin_group() {
+        use nix::unistd::Gid;
+
+        let (wheel, sudo) = (Gid::from_raw(10), Gid::from_raw(27));
+        let (user, users) = (Gid::from_raw(1000), Gid::from_raw(100));
+        assert!(has_admin_group(&[user, sudo], &[wheel, sudo]));
+        assert!(has_admin_group(&[wheel], &[wheel, sudo]));
+        assert!(!has_admin_group(&[user, users], &[wheel, sudo]));
+        // Neither admin group exists on this system
+        assert!(!has_admin_group(&[user, wheel], &[]));
+    }
+
+    #[test]
+    fn info_flags_are_answered_without_the_app() {
+        let text = |arguments: &[&str]| {
+            let arguments: Vec<String> = arguments.iter().map(|a| a.to_string()).collect();
+            info_flag_text(&arguments)
+        };
+        assert_eq!(text(&["--help"]).as_deref(), Some(USAGE));
+        assert_eq!(text(&["-h"]).as_deref(), Some(USAGE));
+        assert!(text(&["--version"]).is_some_and(|text| text.starts_with("fprintui ")));
+        assert_eq!(text(&[]), None);
+        assert_eq!(text(&["--help", "--version"]), None);
+    }
+
+    /// Moves focus like the Tab key does, wrapping around at the end of the window.
+    fn press_tab(window: &gtk4::Window) {
+        if !window.child_focus(gtk4::DirectionType::TabForward) {
+            RootExt::set_focus(window, None::<&gtk4::Widget>);
+            window.child_focus(gtk4::DirectionType::TabForward);
+        }
+    }
+
+    /// Runs the main loop until `done` holds, giving up after a few seconds.
+    fn iterate_until(done: impl Fn() -> bool) {
+        let context = glib::MainContext::default();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while !done() && std::time::Instant::now() < deadline {
+            context.iteration(false);
+        }
+    }
+
+    /// Every widget of type `W` below `root`, in tree order.
+    fn descendants<W: IsA<gtk4::Widget>>(root: &impl IsA<gtk4::Widget>) -> Vec<W> {
+        let mut found = Vec::new();
+        let mut child = root.first_child();
+        while let Some(widget) = child {
+            child = widget.next_sibling();
+            if let Some(widget) = widget.downcast_ref::<W>() {
+                found.push(widget.clone());
+            }
+            found.extend(descendants(&widget));
+        }
+        found
+    }
+
+    /// The first button below `root` labelled `label`.
+    fn button_labelled(root: &impl IsA<gtk4::Widget>, label: &str) -> Button {
+        descendants::<Button>(root)
+            .into_iter()
+            .find(|button| button.label().as_deref() == Some(label))
+            .unwrap_or_else(|| panic!("no {label} button"))
+    }
+
+    // GTK may only be initialized from one thread, so all widget checks share one test. Run
+    // with a display, e.g. `xvfb-run -a cargo test -- --include-ignored`.
+    #[test]
+    #[ignore = "needs a display"]
+    fn widgets() {
+        adw::init().expect("initializing GTK needs a display");
+        gio::resources_register_include!("fprintui.gresource").unwrap();
+        // build_ui and the wizard hand D-Bus work to tokio
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let _runtime = runtime.enter();
+        let app = Application::builder()
+            .application_id(format!("{APP_ID}.Test"))
+            .flags(gio::ApplicationFlags::NON_UNIQUE)
+            .build();
+        app.register(None::<&gio::Cancellable>).unwrap();
+
+        tab_stays_on_the_current_wizard_step(&app);
+        sections_open_over_the_fingerprint_list();
+        enroll_section_starts_the_wizard(&app);
+    }
+
+    fn tab_stays_on_the_current_wizard_step(app: &Application) {
+        let state = AppState::new("user".into());
+        let window = ApplicationWindow::builder().application(app).build();
+        let nav = adw::NavigationView::new();
+        let finger_label = Label::new(Some("Select finger:"));
+        let selector = gtk4::DropDown::from_strings(&["Right Thumb", "Right Index Finger"]);
+        let wizard = create_enroll_wizard(&state, &window, &finger_label, &selector, &nav);
+        window.set_content(Some(&wizard));
+        window.present();
+        iterate_until(|| selector.is_mapped());
+
+        let carousel = descendants::<adw::Carousel>(&wizard).remove(0);
+        let start_button = button_labelled(&carousel.nth_page(0), "Enroll");
+        selector.grab_focus();
+        press_tab(window.upcast_ref());
+        assert!(start_button.has_focus());
+        for _ in 0..4 {
+            press_tab(window.upcast_ref());
+            let focus = RootExt::focus(&window).expect("Tab always focuses something");
+            for later_step in 1..carousel.n_pages() {
+                assert!(!focus.is_ancestor(&carousel.nth_page(later_step)));
+            }
+        }
+        window.destroy();
+    }
+
+    fn sections_open_over_the_fingerprint_list() {
+        let nav = adw::NavigationView::new();
+        for tag in ["main", "enroll", "verify", "delete"] {
+            let child = GBox::new(Orientation::Vertical, 0);
+            nav.add(&adw::NavigationPage::with_tag(&child, tag, tag));
+        }
+        assert_eq!(visible_page_tag(&nav).as_deref(), Some("main"));
+
+        show_page(&nav, "enroll");
+        assert_eq!(visible_page_tag(&nav).as_deref(), Some("enroll"));
+        // Switching sections replaces the pushed page, so Back still leads to the list
+        show_page(&nav, "verify");
+        assert_eq!(visible_page_tag(&nav).as_deref(), Some("verify"));
+        assert_eq!(nav.navigation_stack().n_items(), 2);
+        show_page(&nav, "history");
+        assert_eq!(visible_page_tag(&nav).as_deref(), Some("verify"));
+
+        nav.pop();
+        assert_eq!(visible_page_tag(&nav).as_deref(), Some("main"));
+        show_page(&nav, "delete");
+        show_page(&nav, "main");
+        assert_eq!(visible_page_tag(&nav).as_deref(), Some("main"));
+    }
+
+    fn enroll_section_starts_the_wizard(app: &Application) {
+        build_ui(app);
+        // The welcome and release-notes windows may be open as well
+        let window = app
+            .windows()
+            .into_iter()
+            .find_map(|window| window.downcast::<ApplicationWindow>().ok())
+            .expect("build_ui presents the main window");
+        iterate_until(|| window.is_mapped());
+        let nav = descendants::<adw::NavigationView>(&window).remove(0);
+
+        let section_button = descendants::<Button>(&window)
+            .into_iter()
+            .find(|button| {
+                button.action_name().as_deref() == Some("win.show-page")
+                    && button.label().as_deref() == Some("Enroll")
+            })
+            .expect("no Enroll section button");
+        section_button.emit_clicked();
+        assert_eq!(visible_page_tag(&nav).as_deref(), Some("enroll"));
+
+        let page = nav.visible_page().unwrap();
+        let selector = descendants::<gtk4::DropDown>(&page).remove(0);
+        selector.set_selected(1);
+        let carousel = descendants::<adw::Carousel>(&page).remove(0);
+        button_labelled(&carousel.nth_page(0), "Enroll").emit_clicked();
+        // Without fprintd the session fails right away, but the wizard still leaves the
+        // finger choice for the enrollment step
+        iterate_until(|| carousel.position() > 0.0);
+        assert!(
+            carousel.position() > 0.0,
+            "the wizard stayed on the finger choice"
+        );
+        window.destroy();
+    }
+}
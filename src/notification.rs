@@ -0,0 +1,56 @@
+//! Desktop notifications, e.g. when an enrollment finishes while the window is in the
+//! background.
+//!
+//! `libnotify` talks to the notification daemon directly; `portal-notification` goes through
+//! the XDG portal instead, and wins when both are enabled since it also works inside Flatpak.
+//! Without either feature this is a no-op.
+
+/// Sends a notification unless the user turned them off in preferences.
+pub async fn send_notification(title: &str, body: &str) {
+    if crate::settings::desktop_notifications_enabled() {
+        show(title, body).await;
+    }
+}
+
+#[cfg(all(feature = "libnotify", not(feature = "portal-notification")))]
+async fn show(title: &str, body: &str) {
+    let mut notification = notify_rust::Notification::new();
+    notification
+        .summary(title)
+        .body(body)
+        .appname("Fingerprint Manager")
+        .icon(crate::APP_ID);
+    // notify-rust blocks on the D-Bus round trip
+    let shown = tokio::task::spawn_blocking(move || notification.show().map(drop)).await;
+    match shown {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => {
+            gtk4::glib::g_warning!(crate::LOG_DOMAIN, "Failed to send notification: {}", e)
+        }
+        Err(e) => gtk4::glib::g_warning!(crate::LOG_DOMAIN, "Failed to send notification: {}", e),
+    }
+}
+
+#[cfg(feature = "portal-notification")]
+async fn show(title: &str, body: &str) {
+    use ashpd::desktop::notification::{Notification, NotificationProxy};
+
+    let result = match NotificationProxy::new().await {
+        Ok(proxy) => {
+            proxy
+                .add_notification("fprintui-result", Notification::new(title).body(body))
+                .await
+        }
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        gtk4::glib::g_warning!(
+            crate::LOG_DOMAIN,
+            "Notification portal request failed: {}",
+            e
+        );
+    }
+}
+
+#[cfg(not(any(feature = "libnotify", feature = "portal-notification")))]
+async fn show(_title: &str, _body: &str) {}
@@ -0,0 +1,39 @@
+//! Background portal requests, so a minimized window isn't throttled mid-enrollment.
+//!
+//! Without the `portal` feature these are no-ops.
+
+/// Asks the desktop to let the app keep running in the background.
+#[cfg(feature = "portal")]
+pub async fn request_background(reason: &str) {
+    use ashpd::desktop::background::Background;
+
+    let response = Background::request()
+        .reason(reason)
+        .auto_start(false)
+        .dbus_activatable(false)
+        .send()
+        .await
+        .and_then(|request| request.response());
+    if let Err(e) = response {
+        gtk4::glib::g_warning!(crate::LOG_DOMAIN, "Background portal request failed: {}", e);
+    }
+}
+
+#[cfg(not(feature = "portal"))]
+pub async fn request_background(_reason: &str) {}
+
+/// Drops the background status shown for the app.
+///
+/// The portal has no call to withdraw a granted request, so clearing the status message
+/// is as close to revoking it as we can get.
+#[cfg(feature = "portal")]
+pub async fn release_background() {
+    use ashpd::desktop::background::BackgroundProxy;
+
+    if let Ok(proxy) = BackgroundProxy::new().await {
+        let _ = proxy.set_status("").await;
+    }
+}
+
+#[cfg(not(feature = "portal"))]
+pub async fn release_background() {}
@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use gtk4::prelude::*;
+use gtk4::{gio, glib};
+
+use crate::finger::FingerName;
+use crate::{APP_ID, LOG_DOMAIN};
+
+pub const HAS_SEEN_WELCOME: &str = "has-seen-welcome";
+pub const LAST_ENROLLMENT_STAGE_REACHED: &str = "last-enrollment-stage-reached";
+pub const LAST_ENROLLMENT_FINGER: &str = "last-enrollment-finger";
+pub const ENROLLED_POLL_INTERVAL: &str = "enrolled-poll-interval";
+pub const LAST_SEEN_VERSION: &str = "last-seen-version";
+pub const SHOW_DESKTOP_NOTIFICATIONS: &str = "show-desktop-notifications";
+pub const DEFAULT_FINGER: &str = "default-finger";
+pub const LAST_SELECTED_FINGER: &str = "last-selected-finger";
+pub const DEFAULT_DEVICE_PATH: &str = "default-device-path";
+
+/// The app's settings, or `None` when the schema isn't installed (e.g. under `cargo run`).
+pub fn settings() -> Option<gio::Settings> {
+    gio::SettingsSchemaSource::default()?.lookup(APP_ID, true)?;
+    Some(gio::Settings::new(APP_ID))
+}
+
+/// Puts every key back to its schema default.
+pub fn reset_all() {
+    let Some(settings) = settings() else {
+        return;
+    };
+    if let Some(schema) = settings.settings_schema() {
+        for key in schema.list_keys() {
+            settings.reset(&key);
+        }
+    }
+}
+
+/// Remembers how far the running enrollment got, so an interrupted one can be offered again.
+pub fn record_enrollment_stage(finger: FingerName, stage: u32) {
+    let Some(settings) = settings() else {
+        return;
+    };
+    let result = settings
+        .set_string(LAST_ENROLLMENT_FINGER, finger.as_str())
+        .and_then(|()| settings.set_uint(LAST_ENROLLMENT_STAGE_REACHED, stage));
+    if let Err(e) = result {
+        glib::g_warning!(LOG_DOMAIN, "Failed to save enrollment progress: {}", e);
+    }
+}
+
+/// Forgets the recorded enrollment progress, e.g. once the enrollment completed.
+pub fn clear_enrollment_stage() {
+    let Some(settings) = settings() else {
+        return;
+    };
+    settings.reset(LAST_ENROLLMENT_FINGER);
+    settings.reset(LAST_ENROLLMENT_STAGE_REACHED);
+}
+
+/// The finger and stage of an enrollment that passed at least one stage but never completed.
+pub fn interrupted_enrollment() -> Option<(FingerName, u32)> {
+    let settings = settings()?;
+    let stage = settings.uint(LAST_ENROLLMENT_STAGE_REACHED);
+    if stage == 0 {
+        return None;
+    }
+    let finger = FingerName::try_from(settings.string(LAST_ENROLLMENT_FINGER).as_str()).ok()?;
+    Some((finger, stage))
+}
+
+/// How often to re-count the enrolled fingers, falling back to the schema default.
+pub fn enrolled_poll_interval() -> Duration {
+    let seconds = settings().map_or(60, |settings| settings.uint(ENROLLED_POLL_INTERVAL));
+    Duration::from_secs(seconds.into())
+}
+
+/// Whether to send desktop notifications, on by default.
+pub fn desktop_notifications_enabled() -> bool {
+    settings().is_none_or(|settings| settings.boolean(SHOW_DESKTOP_NOTIFICATIONS))
+}
+
+/// The finger to preselect in finger selectors, if the setting holds a valid one.
+pub fn default_finger() -> Option<FingerName> {
+    FingerName::try_from(settings()?.string(DEFAULT_FINGER).as_str()).ok()
+}
+
+/// The finger to preselect in a new finger selector: the Default Finger preference if the
+/// user set one, otherwise the finger they last picked, otherwise the schema's default finger.
+pub fn preselected_finger() -> Option<FingerName> {
+    let settings = settings()?;
+    let remembered = if settings.user_value(DEFAULT_FINGER).is_some() {
+        None
+    } else {
+        FingerName::try_from(settings.string(LAST_SELECTED_FINGER).as_str()).ok()
+    };
+    remembered.or_else(default_finger)
+}
+
+/// Remembers `finger` as the one to preselect on the next launch.
+pub fn remember_selected_finger(finger: FingerName) {
+    let Some(settings) = settings() else {
+        return;
+    };
+    if let Err(e) = settings.set_string(LAST_SELECTED_FINGER, finger.as_str()) {
+        glib::g_warning!(LOG_DOMAIN, "Failed to save the selected finger: {}", e);
+    }
+}
+
+/// The object path of the device the user picked, if they picked one.
+pub fn default_device_path() -> Option<String> {
+    let path = settings()?.string(DEFAULT_DEVICE_PATH);
+    (!path.is_empty()).then(|| path.to_string())
+}
+
+/// Remembers the device at `path` as the one to use on the next launch.
+pub fn remember_device_path(path: &str) {
+    let Some(settings) = settings() else {
+        return;
+    };
+    if let Err(e) = settings.set_string(DEFAULT_DEVICE_PATH, path) {
+        glib::g_warning!(LOG_DOMAIN, "Failed to save the selected device: {}", e);
+    }
+}
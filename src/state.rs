@@ -0,0 +1,200 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use gtk4::gio;
+use gtk4::gio::prelude::*;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+use crate::activity::ActivityLog;
+use crate::dbus::PermissionLevel;
+use crate::finger::FingerName;
+
+/// Device work that the rest of the UI has to wait for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Enroll,
+    Verify,
+    Delete,
+}
+
+/// Whether fprintd can currently be reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceReadiness {
+    Available,
+    Unavailable,
+}
+
+/// Something that happened which other components may want to react to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppEvent {
+    /// The last known set of enrolled fingers changed.
+    EnrolledFingersChanged,
+    OperationStarted(Operation),
+    /// The operation ended; `true` if it succeeded.
+    OperationCompleted(Operation, bool),
+    DeviceStateChanged(DeviceReadiness),
+}
+
+/// Events queued per subscriber before the slowest one starts missing them.
+const EVENT_CAPACITY: usize = 32;
+
+/// Identifies a listener added with [`AppState::connect_busy_changed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BusyListenerId(u64);
+
+/// UI-thread state shared between pages.
+pub struct AppState {
+    /// The user running the app; see [`crate::dbus::target_user`] for whose fingerprints are
+    /// managed.
+    pub username: String,
+    /// Last known set of enrolled fingers for the current user.
+    pub enrolled_fingers: RefCell<HashSet<FingerName>>,
+    /// Set while a `win.refresh` reload is queued but hasn't run yet.
+    pub refresh_pending: Cell<bool>,
+    /// Result of the startup polkit probe.
+    pub permission_level: Cell<PermissionLevel>,
+    /// The device operation currently running, if any.
+    pub active_operation: Cell<Option<Operation>>,
+    /// Enrollment and verification events for the Activity sidebar, kept for this session only.
+    pub activity: ActivityLog,
+    /// App-wide event bus; see [`AppState::subscribe`].
+    events: broadcast::Sender<AppEvent>,
+    busy_listeners: RefCell<Vec<(BusyListenerId, Box<dyn Fn(bool)>)>>,
+    next_busy_listener: Cell<u64>,
+    /// Cancels the running operation, see [`AppState::cancel_operation`].
+    operation_cancel: RefCell<Option<CancellationToken>>,
+}
+
+impl AppState {
+    /// The only way to build an `AppState`, so its invariants are checked in one place.
+    ///
+    /// There is no connection in here: D-Bus callers go through [`crate::dbus::connect`],
+    /// which hands out the replacement once a lost connection has been forgotten.
+    pub fn new(username: String) -> Rc<Self> {
+        debug_assert!(!username.is_empty(), "AppState needs a username");
+        Rc::new(AppState {
+            username,
+            enrolled_fingers: RefCell::default(),
+            refresh_pending: Cell::new(false),
+            permission_level: Cell::default(),
+            active_operation: Cell::new(None),
+            activity: ActivityLog::default(),
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            busy_listeners: RefCell::default(),
+            next_busy_listener: Cell::new(0),
+            operation_cancel: RefCell::default(),
+        })
+    }
+
+    /// Marks `operation` as running until the returned guard is dropped, or returns `None`
+    /// if another operation is already running.
+    pub fn begin_operation(self: &Rc<Self>, operation: Operation) -> Option<OperationGuard> {
+        if self.active_operation.get().is_some() {
+            return None;
+        }
+        self.active_operation.set(Some(operation));
+        let cancel = CancellationToken::new();
+        self.operation_cancel.replace(Some(cancel.clone()));
+        self.notify_busy(true);
+        self.emit(AppEvent::OperationStarted(operation));
+        Some(OperationGuard {
+            state: self.clone(),
+            operation,
+            succeeded: Cell::new(false),
+            cancel,
+            _hold: gio::Application::default().map(|app| app.hold()),
+        })
+    }
+
+    /// Asks the running operation, if any, to stop and release the device, e.g. because the
+    /// window is closing. It still ends by dropping its guard.
+    pub fn cancel_operation(&self) {
+        if let Some(cancel) = self.operation_cancel.borrow().as_ref() {
+            cancel.cancel();
+        }
+    }
+
+    /// Replaces the last known enrolled fingers, announcing the change if there is one.
+    pub fn set_enrolled_fingers(&self, enrolled: HashSet<FingerName>) {
+        if self.enrolled_fingers.replace(enrolled) != *self.enrolled_fingers.borrow() {
+            self.emit(AppEvent::EnrolledFingersChanged);
+        }
+    }
+
+    /// Drops `finger` from the last known enrolled fingers, announcing the change.
+    pub fn forget_enrolled_finger(&self, finger: &FingerName) {
+        if self.enrolled_fingers.borrow_mut().remove(finger) {
+            self.emit(AppEvent::EnrolledFingersChanged);
+        }
+    }
+
+    /// Receives every event emitted from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<AppEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn emit(&self, event: AppEvent) {
+        // Nobody listening isn't an error
+        let _ = self.events.send(event);
+    }
+
+    /// Calls `f` with `true` when an operation starts and `false` when it ends, until
+    /// [`AppState::disconnect_busy_changed`] is called with the returned id.
+    pub fn connect_busy_changed(&self, f: impl Fn(bool) + 'static) -> BusyListenerId {
+        let id = BusyListenerId(self.next_busy_listener.get());
+        self.next_busy_listener.set(id.0 + 1);
+        self.busy_listeners.borrow_mut().push((id, Box::new(f)));
+        id
+    }
+
+    pub fn disconnect_busy_changed(&self, id: BusyListenerId) {
+        self.busy_listeners
+            .borrow_mut()
+            .retain(|(listener, _)| *listener != id);
+    }
+
+    fn notify_busy(&self, busy: bool) {
+        for (_, listener) in self.busy_listeners.borrow().iter() {
+            listener(busy);
+        }
+    }
+}
+
+/// Ends the operation it was returned for when dropped.
+///
+/// Also holds the application, so closing the last window doesn't quit before the device is
+/// released.
+pub struct OperationGuard {
+    state: Rc<AppState>,
+    operation: Operation,
+    succeeded: Cell<bool>,
+    cancel: CancellationToken,
+    _hold: Option<gio::ApplicationHoldGuard>,
+}
+
+impl OperationGuard {
+    /// Reports the operation as successful once the guard is dropped.
+    pub fn set_succeeded(&self, succeeded: bool) {
+        self.succeeded.set(succeeded);
+    }
+
+    /// Fires when [`AppState::cancel_operation`] is called. Waits within the operation should
+    /// use a child token, so cancelling a single wait doesn't cancel the whole operation.
+    pub fn cancel_token(&self) -> &CancellationToken {
+        &self.cancel
+    }
+}
+
+impl Drop for OperationGuard {
+    fn drop(&mut self) {
+        self.state.active_operation.set(None);
+        self.state.operation_cancel.replace(None);
+        self.state.notify_busy(false);
+        self.state.emit(AppEvent::OperationCompleted(
+            self.operation,
+            self.succeeded.get(),
+        ));
+    }
+}
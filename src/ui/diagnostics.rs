@@ -0,0 +1,227 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use anyhow::Context;
+use gtk4::glib;
+use gtk4::Button;
+use libadwaita as adw;
+use libadwaita::prelude::*;
+
+use crate::dbus::{self, PermissionLevel};
+use crate::{get_enrolled_fingers, run_off_main_thread, LOG_DOMAIN};
+
+/// How a single self-test check came out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum CheckStatus {
+    Passed,
+    Warning,
+    Failed,
+}
+
+impl CheckStatus {
+    fn icon_name(self) -> &'static str {
+        match self {
+            CheckStatus::Passed => "emblem-ok-symbolic",
+            CheckStatus::Warning => "dialog-warning-symbolic",
+            CheckStatus::Failed => "dialog-error-symbolic",
+        }
+    }
+
+    fn css_class(self) -> &'static str {
+        match self {
+            CheckStatus::Passed => "success",
+            CheckStatus::Warning => "warning",
+            CheckStatus::Failed => "error",
+        }
+    }
+}
+
+/// One line of the diagnostic report.
+#[derive(Debug, Clone, serde::Serialize)]
+struct CheckResult {
+    check: &'static str,
+    status: CheckStatus,
+    detail: String,
+}
+
+/// A row showing a check's title, its latest detail and a colored status icon.
+#[derive(Clone)]
+struct CheckRow {
+    title: &'static str,
+    row: adw::ActionRow,
+    icon: gtk4::Image,
+}
+
+impl CheckRow {
+    fn new(title: &'static str) -> Self {
+        let icon = gtk4::Image::new();
+        let row = adw::ActionRow::builder()
+            .title(title)
+            .subtitle("Not run yet")
+            .subtitle_selectable(true)
+            .build();
+        row.add_suffix(&icon);
+        CheckRow { title, row, icon }
+    }
+
+    fn set_running(&self) {
+        self.row.set_subtitle("Checking…");
+        self.icon.set_icon_name(None);
+    }
+
+    fn show(&self, status: CheckStatus, detail: &str) -> CheckResult {
+        self.row.set_subtitle(detail);
+        self.icon.set_icon_name(Some(status.icon_name()));
+        for class in ["success", "warning", "error"] {
+            self.icon.remove_css_class(class);
+        }
+        self.icon.add_css_class(status.css_class());
+        CheckResult {
+            check: self.title,
+            status,
+            detail: detail.to_string(),
+        }
+    }
+}
+
+async fn check_fprintd() -> (CheckStatus, String) {
+    if run_off_main_thread(async { Ok(dbus::fprintd_available().await) })
+        .await
+        .unwrap_or(false)
+    {
+        (
+            CheckStatus::Passed,
+            "fprintd answers on the system bus".into(),
+        )
+    } else {
+        (CheckStatus::Failed, "fprintd could not be reached".into())
+    }
+}
+
+async fn check_device() -> (CheckStatus, String) {
+    let result = run_off_main_thread(async {
+        let conn = dbus::connect()
+            .await
+            .context("connecting to the system bus")?;
+        dbus::get_device_info(&conn)
+            .await
+            .context("reading the fprintd device properties")
+    })
+    .await;
+    match result {
+        Ok(device) => (CheckStatus::Passed, device.name),
+        Err(e) => (CheckStatus::Failed, format!("{e:#}")),
+    }
+}
+
+async fn check_permissions() -> (CheckStatus, String) {
+    let result = run_off_main_thread(async {
+        let conn = dbus::connect()
+            .await
+            .context("connecting to the system bus")?;
+        Ok(dbus::check_fprintd_policykit_rules(&conn).await)
+    })
+    .await;
+    match result {
+        Ok(PermissionLevel::Full) => (CheckStatus::Passed, "Fingerprints can be listed".into()),
+        Ok(PermissionLevel::Restricted) => (
+            CheckStatus::Failed,
+            "polkit denies listing your fingerprints".into(),
+        ),
+        Err(e) => (CheckStatus::Failed, format!("{e:#}")),
+    }
+}
+
+async fn check_enrolled() -> (CheckStatus, String) {
+    match run_off_main_thread(async { Ok(get_enrolled_fingers().await?) }).await {
+        Ok(fingers) if !fingers.is_empty() => {
+            (CheckStatus::Passed, format!("{} enrolled", fingers.len()))
+        }
+        Ok(_) => (CheckStatus::Warning, "No fingers enrolled".into()),
+        Err(e) => (CheckStatus::Failed, format!("{e:#}")),
+    }
+}
+
+/// Builds the Diagnostics page, which checks each link between the app and the sensor.
+pub fn create_diagnostics_page() -> adw::PreferencesPage {
+    let page = adw::PreferencesPage::builder()
+        .title("Diagnostics")
+        .icon_name("system-run-symbolic")
+        .build();
+
+    let fprintd_row = CheckRow::new("fprintd Service");
+    let device_row = CheckRow::new("Fingerprint Device");
+    let permissions_row = CheckRow::new("Permissions");
+    let enrolled_row = CheckRow::new("Enrolled Fingers");
+
+    let group = adw::PreferencesGroup::builder()
+        .title("Self-Test")
+        .description("Checks run one after another; the first failure usually explains the rest")
+        .build();
+    for row in [&fprintd_row, &device_row, &permissions_row, &enrolled_row] {
+        group.add(&row.row);
+    }
+    page.add(&group);
+
+    let run_button = Button::with_label("Run Self-Test");
+    run_button.add_css_class("suggested-action");
+    let copy_button = Button::with_label("Copy Diagnostic Report");
+    copy_button.set_sensitive(false);
+    let buttons = gtk4::Box::new(gtk4::Orientation::Horizontal, 10);
+    buttons.set_halign(gtk4::Align::Center);
+    buttons.append(&run_button);
+    buttons.append(&copy_button);
+    let button_group = adw::PreferencesGroup::new();
+    button_group.add(&buttons);
+    page.add(&button_group);
+
+    let report: Rc<RefCell<Vec<CheckResult>>> = Rc::default();
+
+    let copy_report = report.clone();
+    copy_button.connect_clicked(move |button| {
+        match serde_json::to_string_pretty(&*copy_report.borrow()) {
+            Ok(json) => button.clipboard().set_text(&json),
+            Err(e) => glib::g_warning!(LOG_DOMAIN, "Failed to format diagnostic report: {}", e),
+        }
+    });
+
+    let copy_button_weak = copy_button.downgrade();
+    run_button.connect_clicked(move |run_button| {
+        let rows = [
+            fprintd_row.clone(),
+            device_row.clone(),
+            permissions_row.clone(),
+            enrolled_row.clone(),
+        ];
+        let report = report.clone();
+        let copy_button_weak = copy_button_weak.clone();
+        let run_button = run_button.clone();
+        run_button.set_sensitive(false);
+        glib::spawn_future_local(async move {
+            report.borrow_mut().clear();
+            for row in &rows {
+                row.set_running();
+            }
+            let [fprintd_row, device_row, permissions_row, enrolled_row] = &rows;
+
+            let (status, detail) = check_fprintd().await;
+            report.borrow_mut().push(fprintd_row.show(status, &detail));
+            let (status, detail) = check_device().await;
+            report.borrow_mut().push(device_row.show(status, &detail));
+            let (status, detail) = check_permissions().await;
+            report
+                .borrow_mut()
+                .push(permissions_row.show(status, &detail));
+            let (status, detail) = check_enrolled().await;
+            report.borrow_mut().push(enrolled_row.show(status, &detail));
+
+            run_button.set_sensitive(true);
+            if let Some(button) = copy_button_weak.upgrade() {
+                button.set_sensitive(true);
+            }
+        });
+    });
+
+    page
+}
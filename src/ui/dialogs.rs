@@ -0,0 +1,121 @@
+use gtk4::{ListBox, SelectionMode};
+use libadwaita as adw;
+use libadwaita::prelude::*;
+use libadwaita::ApplicationWindow;
+
+use crate::finger::FingerName;
+
+/// A plain message with a single OK button, replacing the deprecated `gtk4::MessageDialog`.
+pub fn message_dialog(
+    parent: &impl IsA<gtk4::Window>,
+    heading: &str,
+    body: Option<&str>,
+) -> adw::MessageDialog {
+    let dialog = adw::MessageDialog::new(Some(parent), Some(heading), body);
+    dialog.add_response("ok", "_OK");
+    dialog.set_default_response(Some("ok"));
+    dialog.set_close_response("ok");
+    dialog
+}
+
+/// Asks before permanently deleting `fingers`, answering with the `"delete"` or `"cancel"`
+/// response. Enter and Escape both pick `"cancel"`.
+///
+/// This is an `adw::MessageDialog`; `adw::AlertDialog` needs libadwaita 1.5.
+pub fn confirm_delete_dialog(
+    fingers: &[FingerName],
+    parent: &ApplicationWindow,
+) -> adw::MessageDialog {
+    let (heading, body) = match fingers {
+        [finger] => (
+            "Delete Fingerprint?",
+            format!(
+                "The {} fingerprint will be permanently deleted.",
+                finger.display_name()
+            ),
+        ),
+        _ => (
+            "Delete Fingerprints?",
+            format!(
+                "{} fingerprints will be permanently deleted.",
+                fingers.len()
+            ),
+        ),
+    };
+
+    let dialog = adw::MessageDialog::new(Some(parent), Some(heading), Some(&body));
+    dialog.add_responses(&[("cancel", "_Cancel"), ("delete", "_Delete")]);
+    dialog.set_response_appearance("delete", adw::ResponseAppearance::Destructive);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+    dialog
+}
+
+/// Warns that enrolling `finger` again replaces its existing print, answering with the
+/// `"replace"` or `"cancel"` response. Enter and Escape both pick `"cancel"`.
+pub fn already_enrolled_dialog(
+    finger: &FingerName,
+    parent: &ApplicationWindow,
+) -> adw::MessageDialog {
+    let body = format!(
+        "The {} is already enrolled. Replacing it will delete the existing fingerprint.",
+        finger.display_name()
+    );
+
+    let dialog =
+        adw::MessageDialog::new(Some(parent), Some("Finger Already Enrolled"), Some(&body));
+    dialog.add_responses(&[("cancel", "_Cancel"), ("replace", "_Replace")]);
+    dialog.set_response_appearance("replace", adw::ResponseAppearance::Destructive);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+    dialog
+}
+
+/// Asks before an irreversible action, answering with the `"confirm"` or `"cancel"` response.
+/// Enter and Escape both pick `"cancel"`.
+pub fn confirm_destructive_dialog(
+    parent: &impl IsA<gtk4::Window>,
+    heading: &str,
+    body: &str,
+    confirm_label: &str,
+) -> adw::MessageDialog {
+    let dialog = adw::MessageDialog::new(Some(parent), Some(heading), Some(body));
+    dialog.add_responses(&[("cancel", "_Cancel"), ("confirm", confirm_label)]);
+    dialog.set_response_appearance("confirm", adw::ResponseAppearance::Destructive);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+    dialog
+}
+
+/// Asks before wiping all fingerprints, history and settings, answering with the `"reset"`
+/// or `"cancel"` response. Reset stays disabled until the user types RESET.
+pub fn confirm_reset_dialog(parent: &impl IsA<gtk4::Window>) -> adw::MessageDialog {
+    let dialog = adw::MessageDialog::new(
+        Some(parent),
+        Some("Reset All Data?"),
+        Some(
+            "All enrolled fingerprints, the enrollment history and all settings will be \
+             permanently deleted. Type RESET to confirm.",
+        ),
+    );
+    dialog.add_responses(&[("cancel", "_Cancel"), ("reset", "_Reset")]);
+    dialog.set_response_appearance("reset", adw::ResponseAppearance::Destructive);
+    dialog.set_response_enabled("reset", false);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+
+    let entry = adw::EntryRow::builder().title("Confirmation").build();
+    let dialog_weak = dialog.downgrade();
+    entry.connect_changed(move |entry| {
+        if let Some(dialog) = dialog_weak.upgrade() {
+            dialog.set_response_enabled("reset", entry.text() == "RESET");
+        }
+    });
+    let list = ListBox::new();
+    list.set_selection_mode(SelectionMode::None);
+    list.add_css_class("boxed-list");
+    list.append(&entry);
+    dialog.set_extra_child(Some(&list));
+
+    dialog
+}
@@ -0,0 +1,6 @@
+pub mod diagnostics;
+pub mod dialogs;
+pub mod preferences;
+pub mod toasts;
+pub mod welcome;
+pub mod whats_new;
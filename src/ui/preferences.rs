@@ -0,0 +1,374 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use anyhow::Context;
+use gtk4::Button;
+use gtk4::{gio, glib};
+use libadwaita as adw;
+use libadwaita::prelude::*;
+use libadwaita::ApplicationWindow;
+
+use crate::dbus::{self, DeviceInfo};
+use crate::finger::FingerName;
+use crate::history::HistoryStore;
+use crate::state::{AppState, Operation};
+use crate::ui::{diagnostics, dialogs};
+use crate::{
+    delete_all_enrolled_fingers, disable_while_busy, run_off_main_thread, settings, LOG_DOMAIN,
+};
+
+/// Builds the preferences window with the General, About Device and Diagnostics pages.
+pub fn create_preferences_window(
+    parent: &ApplicationWindow,
+    state: &Rc<AppState>,
+) -> adw::PreferencesWindow {
+    let window = adw::PreferencesWindow::builder()
+        .transient_for(parent)
+        .modal(true)
+        .build();
+    let general_page = create_general_page();
+    // Last on the page, below everything that can be changed back
+    general_page.add(&create_danger_zone_group(&window, parent, state));
+    window.add(&general_page);
+    window.add(&create_about_device_page());
+    window.add(&diagnostics::create_diagnostics_page());
+    window
+}
+
+/// Deletes every enrolled print, the history file and all settings.
+async fn reset_all_data() -> anyhow::Result<()> {
+    run_off_main_thread(delete_all_enrolled_fingers()).await?;
+    HistoryStore::clear()?;
+    settings::reset_all();
+    Ok(())
+}
+
+/// A destructive-styled button in a row, for the Danger Zone group.
+fn danger_row(title: &str, subtitle: &str, button_label: &str) -> (adw::ActionRow, Button) {
+    let button = Button::with_label(button_label);
+    button.set_valign(gtk4::Align::Center);
+    button.add_css_class("destructive-action");
+    let row = adw::ActionRow::builder()
+        .title(title)
+        .subtitle(subtitle)
+        .build();
+    row.add_suffix(&button);
+    row.set_activatable_widget(Some(&button));
+    (row, button)
+}
+
+fn create_danger_zone_group(
+    window: &adw::PreferencesWindow,
+    parent: &ApplicationWindow,
+    state: &Rc<AppState>,
+) -> adw::PreferencesGroup {
+    let (delete_row, delete_button) = danger_row(
+        "Delete All Fingerprints",
+        "Remove every fingerprint enrolled for your account",
+        "Delete",
+    );
+    let (history_row, history_button) = danger_row(
+        "Clear History",
+        "Forget past enrollments and verifications",
+        "Clear",
+    );
+    let (reset_row, reset_button) = danger_row(
+        "Reset All Data",
+        "Delete all fingerprints, history and settings",
+        "Reset",
+    );
+    // The history file is written when an enrollment or verification ends, so clearing it
+    // then could race that write
+    for button in [&delete_button, &history_button, &reset_button] {
+        disable_while_busy(state, button);
+    }
+
+    let window_weak = window.downgrade();
+    let parent_weak = parent.downgrade();
+    let delete_state = state.clone();
+    delete_button.connect_clicked(move |_| {
+        let Some(window) = window_weak.upgrade() else {
+            return;
+        };
+        let parent_weak = parent_weak.clone();
+        let state = delete_state.clone();
+        glib::spawn_future_local(async move {
+            let dialog = dialogs::confirm_destructive_dialog(
+                &window,
+                "Delete All Fingerprints?",
+                "Every fingerprint enrolled for your account will be permanently deleted.",
+                "_Delete",
+            );
+            if dialog.choose_future().await != "confirm" {
+                return;
+            }
+            let Some(operation) = state.begin_operation(Operation::Delete) else {
+                return;
+            };
+            let result = run_off_main_thread(delete_all_enrolled_fingers()).await;
+            operation.set_succeeded(result.is_ok());
+            let message = match result {
+                Ok(()) => "All fingerprints were deleted".to_string(),
+                Err(e) => format!("Deletion failed: {e:#}"),
+            };
+            window.add_toast(adw::Toast::new(&message));
+            if let Some(parent) = parent_weak.upgrade() {
+                let _ = parent.activate_action("win.refresh", None);
+            }
+        });
+    });
+
+    let window_weak = window.downgrade();
+    history_button.connect_clicked(move |_| {
+        let Some(window) = window_weak.upgrade() else {
+            return;
+        };
+        glib::spawn_future_local(async move {
+            let dialog = dialogs::confirm_destructive_dialog(
+                &window,
+                "Clear History?",
+                "The record of past enrollments and verifications will be permanently deleted.",
+                "_Clear",
+            );
+            if dialog.choose_future().await != "confirm" {
+                return;
+            }
+            let message = match HistoryStore::clear() {
+                Ok(()) => "History was cleared".to_string(),
+                Err(e) => format!("Clearing history failed: {e}"),
+            };
+            window.add_toast(adw::Toast::new(&message));
+        });
+    });
+
+    let window_weak = window.downgrade();
+    let parent_weak = parent.downgrade();
+    let reset_state = state.clone();
+    reset_button.connect_clicked(move |_| {
+        let Some(window) = window_weak.upgrade() else {
+            return;
+        };
+        let parent_weak = parent_weak.clone();
+        let state = reset_state.clone();
+        glib::spawn_future_local(async move {
+            if dialogs::confirm_reset_dialog(&window).choose_future().await != "reset" {
+                return;
+            }
+            let Some(operation) = state.begin_operation(Operation::Delete) else {
+                return;
+            };
+            let result = reset_all_data().await;
+            operation.set_succeeded(result.is_ok());
+            let message = match result {
+                Ok(()) => "All data was reset".to_string(),
+                Err(e) => format!("Reset failed: {e:#}"),
+            };
+            window.add_toast(adw::Toast::new(&message));
+            if let Some(parent) = parent_weak.upgrade() {
+                let _ = parent.activate_action("win.refresh", None);
+            }
+        });
+    });
+
+    let group = adw::PreferencesGroup::builder()
+        .title("Danger Zone")
+        .description("These actions can't be undone")
+        .build();
+    group.add(&delete_row);
+    group.add(&history_row);
+    group.add(&reset_row);
+    group
+}
+
+fn create_general_page() -> adw::PreferencesPage {
+    let page = adw::PreferencesPage::builder()
+        .title("General")
+        .icon_name("preferences-system-symbolic")
+        .build();
+
+    let notifications_row = adw::SwitchRow::builder()
+        .title("Show Desktop Notifications")
+        .subtitle("When an enrollment finishes in the background")
+        .build();
+
+    // Goes through the key's own action, so the row and GSettings can't drift apart
+    match settings::settings() {
+        Some(settings) => {
+            let action = settings.create_action(settings::SHOW_DESKTOP_NOTIFICATIONS);
+            action
+                .bind_property("state", &notifications_row, "active")
+                .transform_to(|_, state: glib::Variant| state.get::<bool>())
+                .sync_create()
+                .build();
+            notifications_row.connect_active_notify(move |row| {
+                action.change_state(&row.is_active().to_variant());
+            });
+        }
+        None => notifications_row.set_sensitive(false),
+    }
+
+    let group = adw::PreferencesGroup::builder()
+        .title("Notifications")
+        .build();
+    group.add(&notifications_row);
+    page.add(&group);
+
+    let fingers_group = adw::PreferencesGroup::builder().title("Fingers").build();
+    fingers_group.add(&create_default_finger_row());
+    page.add(&fingers_group);
+    page
+}
+
+/// A combo row over every finger, bound to the `default-finger` key.
+///
+/// The row selects by index into [`FingerName::ALL`] while the key holds the canonical name,
+/// so the binding maps between the two.
+fn create_default_finger_row() -> adw::ComboRow {
+    let model = gio::ListStore::new::<gtk4::StringObject>();
+    for finger in FingerName::ALL {
+        model.append(&gtk4::StringObject::new(finger.display_name()));
+    }
+    let row = adw::ComboRow::builder()
+        .title("Default Finger")
+        .subtitle("Preselected when enrolling and verifying")
+        .model(&model)
+        .expression(gtk4::PropertyExpression::new(
+            gtk4::StringObject::static_type(),
+            None::<gtk4::Expression>,
+            "string",
+        ))
+        .build();
+
+    let Some(settings) = settings::settings() else {
+        row.set_sensitive(false);
+        return row;
+    };
+    settings
+        .bind(settings::DEFAULT_FINGER, &row, "selected")
+        .mapping(|variant, _| {
+            let name = variant.str()?;
+            let position = FingerName::ALL
+                .iter()
+                .position(|finger| finger.as_str() == name)?;
+            Some((position as u32).to_value())
+        })
+        .set_mapping(|value, _| {
+            let finger = FingerName::ALL.get(value.get::<u32>().ok()? as usize)?;
+            Some(finger.as_str().to_variant())
+        })
+        .build();
+    row
+}
+
+fn property_row(title: &str) -> adw::ActionRow {
+    adw::ActionRow::builder()
+        .title(title)
+        .subtitle("Unknown")
+        .subtitle_selectable(true)
+        .build()
+}
+
+fn create_about_device_page() -> adw::PreferencesPage {
+    let page = adw::PreferencesPage::builder()
+        .title("About Device")
+        .icon_name("auth-fingerprint-symbolic")
+        .build();
+
+    let name_row = property_row("Device Name");
+    let scan_type_row = property_row("Scan Type");
+    let stages_row = property_row("Enrollment Stages");
+    let path_row = property_row("Device Path");
+    let version_row = property_row("fprintd Version");
+    let firmware_row = property_row("Firmware Version");
+    firmware_row.set_visible(false);
+
+    let group = adw::PreferencesGroup::new();
+    for row in [
+        &name_row,
+        &scan_type_row,
+        &stages_row,
+        &path_row,
+        &version_row,
+        &firmware_row,
+    ] {
+        group.add(row);
+    }
+    page.add(&group);
+
+    let info: Rc<RefCell<Option<DeviceInfo>>> = Rc::default();
+
+    let copy_button = Button::with_label("Copy Debug Info");
+    copy_button.set_halign(gtk4::Align::Center);
+    copy_button.set_sensitive(false);
+    let copy_info = info.clone();
+    copy_button.connect_clicked(move |button| {
+        let Some(info) = copy_info.borrow().clone() else {
+            return;
+        };
+        match serde_json::to_string_pretty(&info) {
+            Ok(json) => button.clipboard().set_text(&json),
+            Err(e) => glib::g_warning!(LOG_DOMAIN, "Failed to format debug info: {}", e),
+        }
+    });
+    let button_group = adw::PreferencesGroup::new();
+    button_group.add(&copy_button);
+    page.add(&button_group);
+
+    // Re-read the device every time the page is shown, in case it was swapped
+    let copy_button_weak = copy_button.downgrade();
+    page.connect_map(move |_| {
+        let name_row = name_row.clone();
+        let scan_type_row = scan_type_row.clone();
+        let stages_row = stages_row.clone();
+        let path_row = path_row.clone();
+        let version_row = version_row.clone();
+        let firmware_row = firmware_row.clone();
+        let info = info.clone();
+        let copy_button_weak = copy_button_weak.clone();
+        glib::spawn_future_local(async move {
+            let result = run_off_main_thread(async {
+                let conn = dbus::connect()
+                    .await
+                    .context("connecting to the system bus")?;
+                dbus::get_device_info(&conn)
+                    .await
+                    .context("reading the fprintd device properties")
+            })
+            .await;
+
+            let loaded = result.as_ref().ok().cloned();
+            match result {
+                Ok(device) => {
+                    let scan_type = match dbus::ScanType::from_property(&device.scan_type) {
+                        dbus::ScanType::Press => "Press",
+                        dbus::ScanType::Swipe => "Swipe",
+                    };
+                    name_row.set_subtitle(&device.name);
+                    scan_type_row.set_subtitle(scan_type);
+                    stages_row.set_subtitle(&device.enroll_stages.to_string());
+                    path_row.set_subtitle(&device.path);
+                    version_row
+                        .set_subtitle(device.fprintd_version.as_deref().unwrap_or("Unknown"));
+                    // Hidden rather than "Unknown", since most drivers never report it
+                    if let Some(version) = &device.firmware_version {
+                        firmware_row.set_subtitle(version);
+                    }
+                    firmware_row.set_visible(device.firmware_version.is_some());
+                }
+                Err(e) => {
+                    name_row.set_subtitle(&format!("Unavailable: {e:#}"));
+                    for row in [&scan_type_row, &stages_row, &path_row, &version_row] {
+                        row.set_subtitle("Unknown");
+                    }
+                    firmware_row.set_visible(false);
+                }
+            }
+            if let Some(button) = copy_button_weak.upgrade() {
+                button.set_sensitive(loaded.is_some());
+            }
+            info.replace(loaded);
+        });
+    });
+
+    page
+}
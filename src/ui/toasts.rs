@@ -0,0 +1,61 @@
+use libadwaita as adw;
+
+use crate::dbus::{EnrollResult, VerifyResult};
+
+const SUCCESS_TIMEOUT: u32 = 2;
+/// Longer, so there is time to read what went wrong.
+const FAILURE_TIMEOUT: u32 = 5;
+
+fn failure_toast(title: &str, retry_action: &str) -> adw::Toast {
+    adw::Toast::builder()
+        .title(title)
+        .timeout(FAILURE_TIMEOUT)
+        .button_label("Retry")
+        .action_name(retry_action)
+        .build()
+}
+
+/// The Retry button of a failure toast activates `win.enroll-finger`; set the finger as its
+/// action target.
+impl From<EnrollResult> for adw::Toast {
+    fn from(result: EnrollResult) -> Self {
+        match result {
+            EnrollResult::Completed => adw::Toast::builder()
+                .title("Enrollment successful")
+                .timeout(SUCCESS_TIMEOUT)
+                .build(),
+            EnrollResult::Cancelled => adw::Toast::builder()
+                .title("Enrollment cancelled")
+                .timeout(SUCCESS_TIMEOUT)
+                .build(),
+            EnrollResult::Error(status) => {
+                failure_toast(&format!("Enrollment failed: {status}"), "win.enroll-finger")
+            }
+        }
+    }
+}
+
+/// The Retry button of a failure toast activates `win.verify-finger`; set the finger as its
+/// action target.
+impl From<VerifyResult> for adw::Toast {
+    fn from(result: VerifyResult) -> Self {
+        match result {
+            VerifyResult::Match => adw::Toast::builder()
+                .title("Verification successful")
+                .timeout(SUCCESS_TIMEOUT)
+                .build(),
+            VerifyResult::NoMatch => {
+                failure_toast("Fingerprint did not match", "win.verify-finger")
+            }
+            VerifyResult::TimedOut => failure_toast("Verification timed out", "win.verify-finger"),
+            VerifyResult::Cancelled => adw::Toast::builder()
+                .title("Verification cancelled")
+                .timeout(SUCCESS_TIMEOUT)
+                .build(),
+            VerifyResult::Error(status) => failure_toast(
+                &format!("Verification failed: {status}"),
+                "win.verify-finger",
+            ),
+        }
+    }
+}
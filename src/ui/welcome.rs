@@ -0,0 +1,118 @@
+use gtk4::gio;
+use gtk4::{Box as GBox, Button, Orientation};
+use libadwaita as adw;
+use libadwaita::prelude::*;
+use libadwaita::ApplicationWindow;
+
+use crate::settings::{settings, HAS_SEEN_WELCOME};
+
+/// Shows the welcome wizard unless the user has already finished or skipped it.
+pub fn present_if_first_run(parent: &ApplicationWindow) {
+    let Some(settings) = settings() else {
+        return;
+    };
+    if settings.boolean(HAS_SEEN_WELCOME) {
+        return;
+    }
+    create_welcome_window(parent, settings).present();
+}
+
+fn welcome_page(icon_name: &str, title: &str, description: &str) -> adw::StatusPage {
+    adw::StatusPage::builder()
+        .icon_name(icon_name)
+        .title(title)
+        .description(description)
+        .hexpand(true)
+        .vexpand(true)
+        .build()
+}
+
+/// A three-page carousel with Skip and Next buttons.
+///
+/// This is a modal `adw::Window`; `adw::Dialog` needs libadwaita 1.5.
+fn create_welcome_window(parent: &ApplicationWindow, settings: gio::Settings) -> adw::Window {
+    let carousel = adw::Carousel::builder().vexpand(true).build();
+    carousel.append(&welcome_page(
+        "auth-fingerprint-symbolic",
+        "What is fprintui?",
+        "fprintui manages the fingerprints fprintd keeps for your account.",
+    ));
+    carousel.append(&welcome_page(
+        "document-edit-symbolic",
+        "How to Enroll",
+        "Pick a finger on the Enroll page, then touch the sensor until every stage is done. \
+         Shift your finger slightly between touches.",
+    ));
+    carousel.append(&welcome_page(
+        "system-lock-screen-symbolic",
+        "What Happens Next",
+        "Once a finger is enrolled, anything using fingerprint authentication through PAM, \
+         like the login screen or sudo, will accept it.",
+    ));
+    let dots = adw::CarouselIndicatorDots::builder()
+        .carousel(&carousel)
+        .build();
+
+    let skip_button = Button::with_label("Skip");
+    let next_button = Button::with_label("Next");
+    next_button.add_css_class("suggested-action");
+    let buttons = GBox::new(Orientation::Horizontal, 10);
+    buttons.set_halign(gtk4::Align::Center);
+    buttons.set_margin_bottom(12);
+    buttons.append(&skip_button);
+    buttons.append(&next_button);
+
+    let content = GBox::new(Orientation::Vertical, 6);
+    content.append(&adw::HeaderBar::new());
+    content.append(&carousel);
+    content.append(&dots);
+    content.append(&buttons);
+
+    let window = adw::Window::builder()
+        .transient_for(parent)
+        .modal(true)
+        .default_width(420)
+        .default_height(420)
+        .content(&content)
+        .build();
+
+    // Closing the window in any way counts as having seen it
+    window.connect_close_request(move |_| {
+        if let Err(e) = settings.set_boolean(HAS_SEEN_WELCOME, true) {
+            gtk4::glib::g_warning!(crate::LOG_DOMAIN, "Failed to save welcome state: {}", e);
+        }
+        gtk4::glib::Propagation::Proceed
+    });
+
+    let window_weak = window.downgrade();
+    skip_button.connect_clicked(move |_| {
+        if let Some(window) = window_weak.upgrade() {
+            window.close();
+        }
+    });
+
+    let next_weak = next_button.downgrade();
+    carousel.connect_page_changed(move |carousel, index| {
+        if let Some(next_button) = next_weak.upgrade() {
+            let last = index + 1 == carousel.n_pages();
+            next_button.set_label(if last { "Done" } else { "Next" });
+        }
+    });
+
+    let window_weak = window.downgrade();
+    let carousel_weak = carousel.downgrade();
+    next_button.connect_clicked(move |_| {
+        let (Some(window), Some(carousel)) = (window_weak.upgrade(), carousel_weak.upgrade())
+        else {
+            return;
+        };
+        let next = carousel.position().round() as u32 + 1;
+        if next < carousel.n_pages() {
+            carousel.scroll_to(&carousel.nth_page(next), true);
+        } else {
+            window.close();
+        }
+    });
+
+    window
+}
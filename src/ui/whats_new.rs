@@ -0,0 +1,81 @@
+use gtk4::{gio, glib};
+use libadwaita::prelude::*;
+use libadwaita::ApplicationWindow;
+
+use crate::settings::{settings, LAST_SEEN_VERSION};
+use crate::ui::dialogs;
+use crate::LOG_DOMAIN;
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Shows the release notes once after the app was updated to a newer version.
+///
+/// A fresh install only records the version; the welcome wizard covers that case.
+pub fn present_if_updated(parent: &ApplicationWindow) {
+    let Some(settings) = settings() else {
+        return;
+    };
+    let last_seen = settings.string(LAST_SEEN_VERSION);
+    if last_seen == VERSION {
+        return;
+    }
+    let notes = (!last_seen.is_empty() && is_newer(VERSION, &last_seen))
+        .then(release_notes)
+        .flatten();
+    let Some(notes) = notes else {
+        remember_version(&settings);
+        return;
+    };
+
+    let dialog = dialogs::message_dialog(parent, &format!("What's New in {VERSION}"), Some(&notes));
+    dialog.connect_response(None, move |_, _| remember_version(&settings));
+    dialog.present();
+}
+
+/// The bundled notes for this version, if any were written for it.
+fn release_notes() -> Option<String> {
+    let path = format!("/org/example/fprintui/whats-new/{VERSION}.txt");
+    let data = gio::resources_lookup_data(&path, gio::ResourceLookupFlags::NONE).ok()?;
+    Some(String::from_utf8_lossy(&data).trim_end().to_string())
+}
+
+fn remember_version(settings: &gio::Settings) {
+    if let Err(e) = settings.set_string(LAST_SEEN_VERSION, VERSION) {
+        glib::g_warning!(LOG_DOMAIN, "Failed to save the last seen version: {}", e);
+    }
+}
+
+/// Compares dotted versions numerically, so `0.10.0` is newer than `0.9.1`. Pre-release
+/// suffixes are ignored.
+fn is_newer(version: &str, than: &str) -> bool {
+    fn parts(version: &str) -> Vec<u64> {
+        version
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    }
+    parts(version) > parts(than)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_numerically() {
+        assert!(is_newer("0.10.0", "0.9.1"));
+        assert!(is_newer("1.0.0", "0.99.99"));
+        assert!(is_newer("0.1.1", "0.1.0"));
+        assert!(!is_newer("0.9.1", "0.10.0"));
+    }
+
+    #[test]
+    fn same_version_is_not_newer() {
+        assert!(!is_newer("0.1.0", "0.1.0"));
+        assert!(!is_newer("0.2.0-beta.1", "0.2.0"));
+        assert!(!is_newer("0.2.0+git", "0.2.0-rc1"));
+    }
+}